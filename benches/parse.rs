@@ -0,0 +1,24 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use oci_imgref::image::Image;
+
+const INPUTS: &[&str] = &[
+    "ubuntu",
+    "library/ubuntu:22.04",
+    "docker.io/library/ubuntu:latest",
+    "quay.io:443/foo/bar:latest@sha256:e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855",
+    "gcr.io/project/team/app@sha256:e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855",
+    "localhost:5000/my-app:1.0",
+];
+
+fn parse(c: &mut Criterion) {
+    c.bench_function("parse", |b| {
+        b.iter(|| {
+            for input in INPUTS {
+                let _ = black_box(black_box(input).parse::<Image>());
+            }
+        })
+    });
+}
+
+criterion_group!(benches, parse);
+criterion_main!(benches);
@@ -18,22 +18,502 @@
 //! assert_eq!(image.digest.unwrap().to_string(), "sha256:e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855");
 //! ```
 
+use alloc::borrow::Cow;
+use alloc::collections::BTreeSet;
+use alloc::format;
 use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+use core::marker::PhantomData;
+use core::num::NonZeroU16;
+use core::ops::Range;
 use core::{fmt::Display, hash::Hash, str::FromStr};
 
-use crate::{digest::Digest, repository::Repository};
+use crate::digest::Digest;
+use crate::options::ParseOptions;
+use crate::registry::{Registry, RegistryPattern, Scheme};
+use crate::repository::{Repository, Slashes};
 
 /// an image parsing error
 #[derive(Copy, Clone, Debug, PartialEq, Eq, thiserror::Error, displaydoc::Display)]
 pub enum Error {
-    /// invalid registry: {0}
+    /// invalid repository: {0}
     Repository(#[from] crate::repository::Error),
 
-    /// invalid tag
+    /// invalid tag (expected `[A-Za-z0-9_][A-Za-z0-9._-]*`, at most 128 characters)
     Tag,
 
-    /// invalid digest: {0}
+    /// invalid tag: ends with `.` or `-` (allowed by the specification, but forbidden by the parse options)
+    TagSeparator,
+
+    /// invalid digest: {0} (expected `algorithm:hex`)
     Digest(#[from] crate::digest::Error),
+
+    /// invalid UTF-8
+    Utf8,
+
+    /// unexpected whitespace
+    Whitespace,
+
+    /// illegal character: {found} (expected printable ASCII)
+    IllegalCharacter {
+        /// the class of the first illegal character
+        found: CharClass,
+    },
+
+    /// digest algorithm below the required minimum
+    WeakAlgorithm,
+
+    /// missing registry
+    MissingRegistry,
+
+    /// missing tag or digest
+    Underspecified,
+
+    /// both tag and digest present
+    TagAndDigest,
+
+    /// `library` organization outside Docker Hub
+    ForeignLibrary,
+
+    // Only converting to an `http::Uri` produces this, but the variant is
+    // always present so that enabling `http` cannot break exhaustive matches.
+    /// invalid URI
+    Uri,
+
+    // Likewise, only converting from a `url::Url` produces this.
+    /// invalid URL (expected `http[s]://host[:port]/path[#reference]`)
+    Url,
+}
+
+impl Hash for Error {
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+        core::mem::discriminant(self).hash(state);
+
+        match self {
+            Self::Repository(e) => e.hash(state),
+
+            // Digest errors are not `Hash`, but hashing their variant alone
+            // is still consistent with `Eq`.
+            Self::Digest(e) => core::mem::discriminant(e).hash(state),
+
+            Self::IllegalCharacter { found } => found.hash(state),
+
+            _ => (),
+        }
+    }
+}
+
+/// a class of characters never valid in an image reference
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, displaydoc::Display)]
+pub enum CharClass {
+    /// control character
+    Control,
+
+    /// non-ASCII character
+    NonAscii,
+}
+
+impl CharClass {
+    /// Returns the class of `c`, if it is never valid in a reference
+    ///
+    /// Whitespace is not classified here, as it is reported separately by
+    /// [`Error::Whitespace`].
+    ///
+    /// ```rust
+    /// use oci_imgref::image::CharClass;
+    ///
+    /// assert_eq!(CharClass::of('\0'), Some(CharClass::Control));
+    /// assert_eq!(CharClass::of('é'), Some(CharClass::NonAscii));
+    /// assert_eq!(CharClass::of('\n'), None);
+    /// assert_eq!(CharClass::of('a'), None);
+    /// ```
+    pub fn of(c: char) -> Option<Self> {
+        match c {
+            c if c.is_whitespace() => None,
+            c if c.is_ascii_control() => Some(Self::Control),
+            c if !c.is_ascii() => Some(Self::NonAscii),
+            _ => None,
+        }
+    }
+}
+
+/// the tag and digest of an image reference
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Reference {
+    /// neither a tag nor a digest (i.e. `foo/bar`)
+    None,
+
+    /// only a tag (i.e. `latest` in `foo/bar:latest`)
+    Tag(String),
+
+    /// only a digest (i.e. `sha256:deadbeef` in `foo/bar@sha256:deadbeef`)
+    Digest(Digest),
+
+    /// both a tag and a digest (i.e. `foo/bar:latest@sha256:deadbeef`)
+    Both(String, Digest),
+}
+
+impl Reference {
+    /// Returns the tag, if any
+    #[inline]
+    pub fn as_tag(&self) -> Option<&str> {
+        match self {
+            Self::Tag(tag) | Self::Both(tag, _) => Some(tag),
+            Self::None | Self::Digest(_) => None,
+        }
+    }
+
+    /// Returns the digest, if any
+    #[inline]
+    pub const fn as_digest(&self) -> Option<&Digest> {
+        match self {
+            Self::Digest(digest) | Self::Both(_, digest) => Some(digest),
+            Self::None | Self::Tag(_) => None,
+        }
+    }
+}
+
+/// a pending change to the tag and digest of an image
+///
+/// Returned by [`Image::reference_mut`]. Changes are staged and only applied
+/// to the image, after validation, by [`ReferenceMut::commit`]; dropping the
+/// guard discards them.
+#[derive(Debug)]
+#[must_use = "changes are only applied by `commit`"]
+pub struct ReferenceMut<'a> {
+    image: &'a mut Image,
+    tag: Option<String>,
+    digest: Option<Digest>,
+}
+
+impl ReferenceMut<'_> {
+    /// Stages a new tag, or its removal
+    pub fn tag(&mut self, tag: Option<&str>) -> &mut Self {
+        self.tag = tag.map(Into::into);
+        self
+    }
+
+    /// Stages a new digest, or its removal
+    pub fn digest(&mut self, digest: Option<Digest>) -> &mut Self {
+        self.digest = digest;
+        self
+    }
+
+    /// Validates the staged tag and digest and applies both together
+    ///
+    /// On error the image is left unchanged.
+    pub fn commit(self) -> Result<(), Error> {
+        if let Some(tag) = &self.tag {
+            self::tag(tag)?;
+        }
+
+        self.image.tag = self.tag.map(Cow::Owned);
+        self.image.digest = self.digest;
+        Ok(())
+    }
+}
+
+/// marks an [`ImageBuilder`] given only validated components
+#[derive(Copy, Clone, Debug)]
+pub struct Checked;
+
+/// marks an [`ImageBuilder`] given strings still to be validated
+#[derive(Copy, Clone, Debug)]
+pub struct Unchecked;
+
+/// a builder for [`Image`], created by [`Image::builder`]
+///
+/// A typed [`Digest`] keeps the builder [`Checked`], so that
+/// [`build`](ImageBuilder::build) cannot fail. Methods taking strings
+/// validate them and make the builder [`Unchecked`], whose `build` returns
+/// the first error, if any. So does a typed [`Registry`], since the
+/// repository must be checked again once combined with it.
+#[derive(Clone, Debug)]
+#[must_use]
+pub struct ImageBuilder<S = Checked> {
+    image: Image,
+    error: Option<Error>,
+    state: PhantomData<S>,
+}
+
+impl<S> ImageBuilder<S> {
+    /// Sets the registry
+    ///
+    /// The registry may push the repository over the length limit, or be a
+    /// single-label host that reads back as an organization, so the
+    /// repository is checked again.
+    pub fn registry(self, registry: Registry) -> ImageBuilder<Unchecked> {
+        self.apply(|image| {
+            image.repository.registry = Some(registry);
+            Ok(crate::repository::check(&image.repository)?)
+        })
+    }
+
+    /// Sets the digest
+    pub fn digest(mut self, digest: Digest) -> Self {
+        self.image.digest = Some(digest);
+        self
+    }
+
+    /// Parses and sets the registry
+    pub fn registry_str(self, registry: &str) -> ImageBuilder<Unchecked> {
+        match registry.parse::<Registry>() {
+            Ok(registry) => self.registry(registry),
+            Err(e) => self.apply(|_| Err(Error::Repository(e.into()))),
+        }
+    }
+
+    /// Validates and sets the tag
+    pub fn tag(self, tag: &str) -> ImageBuilder<Unchecked> {
+        self.apply(|image| image.set_tag(tag))
+    }
+
+    /// Parses and sets the digest
+    pub fn digest_str(self, digest: &str) -> ImageBuilder<Unchecked> {
+        self.apply(|image| image.set_digest(digest))
+    }
+
+    fn apply(self, f: impl FnOnce(&mut Image) -> Result<(), Error>) -> ImageBuilder<Unchecked> {
+        let mut image = self.image;
+        let result = f(&mut image);
+
+        ImageBuilder {
+            image,
+            error: self.error.or(result.err()),
+            state: PhantomData,
+        }
+    }
+}
+
+impl ImageBuilder<Checked> {
+    /// Returns the image
+    pub fn build(self) -> Image {
+        self.image
+    }
+}
+
+impl ImageBuilder<Unchecked> {
+    /// Returns the image, or the first error from a string component
+    pub fn build(self) -> Result<Image, Error> {
+        match self.error {
+            Some(error) => Err(error),
+            None => Ok(self.image),
+        }
+    }
+}
+
+/// a component of an image reference, as yielded by [`Image::components`]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Component<'a> {
+    /// the registry
+    Registry(&'a Registry),
+
+    /// the organization
+    Organization(&'a str),
+
+    /// the container
+    Container(&'a str),
+
+    /// the tag
+    Tag(&'a str),
+
+    /// the digest
+    Digest(&'a Digest),
+}
+
+/// a wrapper comparing and hashing images by their canonical form
+///
+/// Equality follows [`Image::canonical_eq`] and hashing uses the string
+/// form of [`Image::to_canonical`], so the two stay consistent. This lets
+/// differently spelled references to the same image share a map key.
+///
+/// ```rust
+/// use oci_imgref::image::{Canonical, Image};
+///
+/// let a: Image = "ubuntu".parse().unwrap();
+/// let b: Image = "docker.io/library/ubuntu:latest".parse().unwrap();
+/// assert_eq!(Canonical(a), Canonical(b));
+/// ```
+#[derive(Clone, Debug)]
+pub struct Canonical<T>(pub T);
+
+impl PartialEq for Canonical<Image> {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.0.canonical_eq(&other.0)
+    }
+}
+
+impl Eq for Canonical<Image> {}
+
+impl Hash for Canonical<Image> {
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+        self.0.to_canonical().to_string().hash(state)
+    }
+}
+
+/// a glob pattern over image references
+///
+/// The pattern is matched against the [`Display`] form of an image, in
+/// which `*` matches any run of characters, including `/`, `:` and `@`, and
+/// every other character matches itself. Each `*` matches as little as
+/// possible, leaving the rest to later ones.
+///
+/// ```rust
+/// use oci_imgref::image::{Image, ImagePattern};
+///
+/// let pattern = ImagePattern::from("quay.io/*/bar:*");
+/// let image: Image = "quay.io/foo/bar:v1".parse().unwrap();
+///
+/// let captures = pattern.captures(&image).unwrap();
+/// assert_eq!(captures.get(0), Some("foo"));
+/// assert_eq!(captures.get(1), Some("v1"));
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ImagePattern(pub String);
+
+impl ImagePattern {
+    /// Returns `true` if the pattern matches `image`
+    #[inline]
+    pub fn matches(&self, image: &Image) -> bool {
+        self.captures(image).is_some()
+    }
+
+    /// Returns what each `*` matched, or `None` if the pattern does not match
+    pub fn captures(&self, image: &Image) -> Option<Captures> {
+        let image = image.to_string();
+        let mut ranges = Vec::new();
+
+        glob(self.0.as_bytes(), image.as_bytes(), &mut ranges).then_some(Captures { image, ranges })
+    }
+}
+
+impl From<&str> for ImagePattern {
+    #[inline]
+    fn from(value: &str) -> Self {
+        Self(value.into())
+    }
+}
+
+impl From<String> for ImagePattern {
+    #[inline]
+    fn from(value: String) -> Self {
+        Self(value)
+    }
+}
+
+impl Display for ImagePattern {
+    #[inline]
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// the substrings matched by the wildcards of an [`ImagePattern`]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Captures {
+    /// the image reference that was matched
+    pub image: String,
+
+    /// the range of `image` matched by each `*`, in pattern order
+    pub ranges: Vec<Range<usize>>,
+}
+
+impl Captures {
+    /// Returns what the `index`th `*` matched
+    #[inline]
+    pub fn get(&self, index: usize) -> Option<&str> {
+        self.ranges
+            .get(index)
+            .and_then(|range| self.image.get(range.clone()))
+    }
+
+    /// Returns what each `*` matched, in pattern order
+    pub fn iter(&self) -> impl Iterator<Item = &str> + '_ {
+        (0..self.ranges.len()).filter_map(|index| self.get(index))
+    }
+}
+
+/// Matches `text` against `pattern`, recording the range matched by each `*`
+fn glob(pattern: &[u8], text: &[u8], ranges: &mut Vec<Range<usize>>) -> bool {
+    let (mut p, mut t) = (0, 0);
+    let mut star = None;
+
+    // Backtracking to the last `*` alone suffices: earlier ones already
+    // matched as little as they could.
+    while t < text.len() {
+        match pattern.get(p) {
+            Some(b'*') => {
+                ranges.push(t..t);
+                star = Some((p + 1, ranges.len() - 1));
+                p += 1;
+            }
+
+            Some(&c) if c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+
+            _ => match star {
+                None => return false,
+                Some((after, index)) => {
+                    ranges.truncate(index + 1);
+                    ranges[index].end += 1;
+                    t = ranges[index].end;
+                    p = after;
+                }
+            },
+        }
+    }
+
+    while pattern.get(p) == Some(&b'*') {
+        ranges.push(t..t);
+        p += 1;
+    }
+
+    p == pattern.len()
+}
+
+/// a digest algorithm, ordered from weakest to strongest
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Algorithm {
+    /// SHA-256
+    Sha256,
+
+    /// SHA-384
+    Sha384,
+
+    /// SHA-512
+    Sha512,
+}
+
+impl Algorithm {
+    /// Returns the algorithm of a digest
+    ///
+    /// Returns `None` for algorithms without a defined strength.
+    pub fn of(digest: &Digest) -> Option<Self> {
+        let digest = DigestBuf::of(digest)?;
+        Self::named(digest.parts().0)
+    }
+
+    fn named(name: &[u8]) -> Option<Self> {
+        match name {
+            b"sha256" => Some(Self::Sha256),
+            b"sha384" => Some(Self::Sha384),
+            b"sha512" => Some(Self::Sha512),
+            _ => None,
+        }
+    }
+
+    /// the length of the hex encoding of a hash
+    const fn hex_len(self) -> usize {
+        match self {
+            Self::Sha256 => 64,
+            Self::Sha384 => 96,
+            Self::Sha512 => 128,
+        }
+    }
 }
 
 /// a container image reference
@@ -59,88 +539,1954 @@ pub enum Error {
 /// // Parse from a string
 /// let image: Image = "docker.io/library/ubuntu:latest".parse().unwrap();
 /// ```
+///
+/// # Static references
+///
+/// The string fields are [`Cow`]s, so a reference fixed at compile time
+/// can borrow `'static` literals instead of allocating, while parsed
+/// references own their strings. Which one a field holds never affects
+/// formatting, comparison, hashing or serialization.
+///
+/// ```rust
+/// use std::borrow::Cow;
+/// use oci_imgref::image::Image;
+/// use oci_imgref::registry::Registry;
+/// use oci_imgref::repository::Repository;
+///
+/// const UBUNTU: Image = Image {
+///     repository: Repository::from_static(Some(Registry::docker_hub()), Some("library"), "ubuntu"),
+///     tag: Some(Cow::Borrowed("latest")),
+///     digest: None,
+/// };
+///
+/// assert_eq!(UBUNTU, "docker.io/library/ubuntu:latest".parse().unwrap());
+/// ```
+///
+/// # Whitespace
+///
+/// Whitespace is never valid in a reference. Rather than failing on whichever
+/// component it lands in, parsing rejects any leading, trailing or embedded
+/// whitespace up front with [`Error::Whitespace`]. Callers handling pasted
+/// input should trim it first.
+///
+/// Control and non-ASCII characters are likewise rejected up front, with
+/// [`Error::IllegalCharacter`] naming their [`CharClass`], so that logs can
+/// tell hostile input apart from a mistyped reference.
+///
+/// # Digests
+///
+/// The OCI image specification requires digests to be lowercase, so
+/// parsing rejects uppercase digests. This covers the algorithm as well as
+/// the hex: `SHA256:...` is an error rather than another spelling of
+/// `sha256:...`. Since some tools emit uppercase hex,
+/// [`ParseOptions::lowercase_digest`] lowercases them first instead.
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
-#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
-#[cfg_attr(feature = "serde", serde(try_from = "String", into = "String"))]
 pub struct Image {
     /// the repository (i.e. `quay.io:1234/foo/bar` in `quay.io:1234/foo/bar:latest`)
     pub repository: Repository,
 
     /// the tag (i.e. `latest` in `foo/bar:latest`)
-    pub tag: Option<String>,
+    pub tag: Option<Cow<'static, str>>,
 
     /// the digest (i.e. `sha256:deadbeef` in `foo/bar@sha256:deadbeef`)
     pub digest: Option<Digest>,
 }
 
-impl From<Image> for String {
-    fn from(value: Image) -> Self {
-        value.to_string()
-    }
-}
+impl Image {
+    /// Parses an image reference with the given options
+    ///
+    /// With the default options this is identical to [`str::parse`].
+    pub fn parse_with(input: &str, options: ParseOptions) -> Result<Self, Error> {
+        let collapsed;
+        let input = match options.collapse_slashes && !options.strict && input.contains("//") {
+            true => {
+                collapsed = collapse_slashes(input);
+                &collapsed
+            }
+            false => input,
+        };
 
-impl Display for Image {
-    #[inline(always)]
-    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
-        write!(f, "{}", self.repository)?;
+        let lowercased;
+        let input = match options.lowercase_digest && !options.strict {
+            true => match lowercase_digest(input) {
+                Some(lower) => {
+                    lowercased = lower;
+                    &lowercased
+                }
+                None => input,
+            },
+            false => input,
+        };
 
-        if let Some(tag) = &self.tag {
-            write!(f, ":{}", tag)?;
+        let mut image: Self = input.parse()?;
+
+        if options.single_label_registry && image.repository.registry.is_none() {
+            if let Some(host) = image.repository.organization.take() {
+                let registry = host.parse().map_err(crate::repository::Error::Registry)?;
+                image.repository.registry = Some(registry);
+            }
         }
 
-        if let Some(digest) = &self.digest {
-            write!(f, "@{}", digest)?;
+        if options.forbid_tag_and_digest && image.tag.is_some() && image.digest.is_some() {
+            return Err(Error::TagAndDigest);
         }
 
-        Ok(())
-    }
-}
+        let trailing = image
+            .tag
+            .as_deref()
+            .is_some_and(|t| t.ends_with(['.', '-']));
+        if options.forbid_trailing_separator && trailing {
+            return Err(Error::TagSeparator);
+        }
 
-impl FromStr for Image {
-    type Err = Error;
+        if options.require_registry && image.repository.registry.is_none() {
+            return Err(Error::MissingRegistry);
+        }
 
-    fn from_str(mut from: &str) -> Result<Self, Self::Err> {
-        let mut digest = None;
-        if let Some((prefix, dig)) = from.rsplit_once('@') {
-            digest = Some(dig.parse()?);
-            from = prefix;
+        if options.forbid_foreign_library {
+            let repository = &image.repository;
+            let foreign = repository
+                .registry
+                .as_ref()
+                .is_some_and(|r| !r.is_default());
+            if foreign && repository.organization.as_deref() == Some("library") {
+                return Err(Error::ForeignLibrary);
+            }
         }
 
-        let mut tag = None;
-        if let Some((prefix, lbl)) = from.rsplit_once(':') {
-            if !lbl.contains('/') {
-                if lbl.is_empty() {
-                    return Err(Error::Tag);
-                }
+        if options.strict {
+            image.validate_strict()?;
+        }
 
-                for (i, c) in lbl.chars().enumerate() {
-                    match (i, c) {
-                        (i, _) if i > 127 => return Err(Error::Tag),
-                        (_, 'a'..='z' | 'A'..='Z' | '0'..='9' | '_') => (),
-                        (0, _) => return Err(Error::Tag),
-                        (_, '.' | '-') => (),
-                        _ => return Err(Error::Tag),
-                    }
-                }
+        if options.lowercase_host {
+            if let Some(registry) = &mut image.repository.registry {
+                crate::registry::lowercase(&mut registry.host);
+            }
+        }
+
+        if options.default_docker_hub {
+            let repository = &mut image.repository;
+            let registry = repository.registry.get_or_insert_with(Registry::docker_hub);
 
-                tag = Some(lbl.to_string());
-                from = prefix;
+            if registry.is_default() {
+                *registry = Registry::docker_hub();
+                if repository.organization.is_none() {
+                    repository.organization = Some("library".into());
+                }
             }
         }
 
-        Ok(Self {
-            repository: Repository::from_str(from)?,
-            tag,
-            digest,
-        })
+        if options.default_tag_latest && image.tag.is_none() && image.digest.is_none() {
+            image.tag = Some("latest".into());
+        }
+
+        Ok(image)
     }
-}
 
-impl TryFrom<String> for Image {
-    type Error = Error;
+    /// Returns `true` if the image is referenced by a tag that no digest pins
+    ///
+    /// Tags such as `latest` can be moved to different content at any time,
+    /// so pulling such a reference is not reproducible.
+    ///
+    /// ```rust
+    /// use oci_imgref::image::Image;
+    ///
+    /// let image: Image = "quay.io/foo/bar:latest".parse().unwrap();
+    /// assert!(image.is_mutable_tag());
+    /// ```
+    #[inline]
+    pub const fn is_mutable_tag(&self) -> bool {
+        self.tag.is_some() && self.digest.is_none()
+    }
 
-    #[inline(always)]
-    fn try_from(value: String) -> Result<Self, Self::Error> {
-        value.parse()
+    /// Returns `true` if the image is referenced by one of the `mutable` tags
+    /// and no digest pins it
+    ///
+    /// Unlike [`Image::is_mutable_tag`], which distrusts every tag, this
+    /// assumes tags outside `mutable` (such as release versions) are never
+    /// moved by convention. Tags are compared case-sensitively.
+    ///
+    /// ```rust
+    /// use oci_imgref::image::Image;
+    ///
+    /// let mutable = ["latest", "main", "nightly"];
+    ///
+    /// let image: Image = "quay.io/foo/bar:main".parse().unwrap();
+    /// assert!(image.is_mutable_tag_in(&mutable));
+    ///
+    /// let image: Image = "quay.io/foo/bar:v1.2.3".parse().unwrap();
+    /// assert!(!image.is_mutable_tag_in(&mutable));
+    /// ```
+    #[inline]
+    pub fn is_mutable_tag_in(&self, mutable: &[&str]) -> bool {
+        self.digest.is_none()
+            && self
+                .tag
+                .as_deref()
+                .is_some_and(|tag| mutable.contains(&tag))
+    }
+
+    /// Returns `true` if the tag looks like it names fixed content
+    ///
+    /// Tags are never truly immutable, but some conventions make moving them
+    /// unlikely. The heuristic accepts:
+    ///
+    /// - a commit hash, i.e. `sha-` followed by 7 to 40 lowercase hex digits
+    ///   (`sha-abc1234`)
+    /// - a date stamp, i.e. 8 to 14 digits (`20240115` or `20240115093000`)
+    ///
+    /// Images without a tag return `false`, whether or not a digest pins them.
+    ///
+    /// ```rust
+    /// use oci_imgref::image::Image;
+    ///
+    /// let image: Image = "quay.io/foo/bar:sha-abc1234".parse().unwrap();
+    /// assert!(image.tag_looks_immutable());
+    ///
+    /// let image: Image = "quay.io/foo/bar:latest".parse().unwrap();
+    /// assert!(!image.tag_looks_immutable());
+    /// ```
+    pub fn tag_looks_immutable(&self) -> bool {
+        let Some(tag) = self.tag.as_deref() else {
+            return false;
+        };
+
+        let hex = |b: u8| b.is_ascii_digit() || (b'a'..=b'f').contains(&b);
+
+        match tag.strip_prefix("sha-") {
+            Some(sha) => (7..=40).contains(&sha.len()) && sha.bytes().all(hex),
+            None => (8..=14).contains(&tag.len()) && tag.bytes().all(|b| b.is_ascii_digit()),
+        }
+    }
+
+    /// Returns `true` if `pattern` matches the image
+    ///
+    /// See [`ImagePattern`] for the pattern syntax.
+    #[inline]
+    pub fn matches(&self, pattern: &ImagePattern) -> bool {
+        pattern.matches(self)
+    }
+
+    /// Compares the tags of two images as semantic versions
+    ///
+    /// Returns `None` unless both tags parse as [semver](https://semver.org)
+    /// versions, so that a lexical order is never mistaken for a release
+    /// order. Note that a leading `v`, as in `v1.0.0`, is not semver.
+    ///
+    /// ```rust
+    /// use core::cmp::Ordering;
+    /// use oci_imgref::image::Image;
+    ///
+    /// let a: Image = "quay.io/foo/bar:1.9.0".parse().unwrap();
+    /// let b: Image = "quay.io/foo/bar:1.10.0".parse().unwrap();
+    /// assert_eq!(a.cmp_tags_semver(&b), Some(Ordering::Less));
+    /// ```
+    #[cfg(feature = "semver")]
+    pub fn cmp_tags_semver(&self, other: &Self) -> Option<Ordering> {
+        let version = |image: &Self| image.tag.as_deref()?.parse::<semver::Version>().ok();
+        Some(version(self)?.cmp(&version(other)?))
+    }
+
+    /// Compares the tags of two images as dates
+    ///
+    /// Tags are date-like if they are a whole date, written as `YYYYMMDD` or
+    /// as `YYYY-MM-DD` with `-`, `.` or `_` as separator. Different formats
+    /// compare chronologically with each other. Returns `None` unless both
+    /// tags are date-like, like [`Image::cmp_tags_semver`]. Months must be
+    /// 1-12 and days 1-31, but days are not checked against the month.
+    ///
+    /// ```rust
+    /// use core::cmp::Ordering;
+    /// use oci_imgref::image::Image;
+    ///
+    /// let a: Image = "quay.io/foo/bar:2024-06-01".parse().unwrap();
+    /// let b: Image = "quay.io/foo/bar:20241231".parse().unwrap();
+    /// assert_eq!(a.cmp_tags_datelike(&b), Some(Ordering::Less));
+    /// ```
+    pub fn cmp_tags_datelike(&self, other: &Self) -> Option<Ordering> {
+        let date = |image: &Self| date(image.tag.as_deref()?);
+        Some(date(self)?.cmp(&date(other)?))
+    }
+
+    /// Returns the repository, with registry, but without tag or digest
+    ///
+    /// All tags and digests of an image belong to the same family, which is
+    /// what dashboards usually group by.
+    ///
+    /// ```rust
+    /// use oci_imgref::image::Image;
+    ///
+    /// let image: Image = "quay.io/foo/bar:latest".parse().unwrap();
+    /// assert_eq!(image.family(), "quay.io/foo/bar");
+    /// ```
+    #[inline]
+    pub fn family(&self) -> String {
+        self.repository.to_string()
+    }
+
+    /// Returns the tag, or `default` if the image has none
+    ///
+    /// ```rust
+    /// use oci_imgref::image::Image;
+    ///
+    /// let image: Image = "quay.io/foo/bar".parse().unwrap();
+    /// assert_eq!(image.tag_or("stable"), "stable");
+    /// ```
+    #[inline]
+    pub fn tag_or<'a>(&'a self, default: &'a str) -> &'a str {
+        self.tag.as_deref().unwrap_or(default)
+    }
+
+    /// Rejects digests using an algorithm weaker than `min`
+    ///
+    /// Images without a digest pass; digests with an algorithm of unknown
+    /// strength fail.
+    ///
+    /// ```rust
+    /// use oci_imgref::image::{Algorithm, Error, Image};
+    ///
+    /// let image: Image = "foo@sha256:e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855".parse().unwrap();
+    /// assert_eq!(image.require_min_algorithm(Algorithm::Sha256), Ok(()));
+    /// assert_eq!(image.require_min_algorithm(Algorithm::Sha512), Err(Error::WeakAlgorithm));
+    /// ```
+    pub fn require_min_algorithm(&self, min: Algorithm) -> Result<(), Error> {
+        match &self.digest {
+            None => Ok(()),
+            Some(digest) => match Algorithm::of(digest) {
+                Some(algorithm) if algorithm >= min => Ok(()),
+                _ => Err(Error::WeakAlgorithm),
+            },
+        }
+    }
+
+    /// Returns the URL of the image's manifest in the registry v2 API
+    ///
+    /// The manifest is referenced by digest when there is one, and by tag
+    /// otherwise, falling back to `latest` when the image has neither.
+    ///
+    /// ```rust
+    /// use oci_imgref::image::Image;
+    /// use oci_imgref::registry::Scheme;
+    ///
+    /// let image: Image = "quay.io/foo/bar:1.0".parse().unwrap();
+    /// assert_eq!(
+    ///     image.manifest_url(Scheme::Https).unwrap(),
+    ///     "https://quay.io:443/v2/foo/bar/manifests/1.0",
+    /// );
+    /// ```
+    pub fn manifest_url(&self, scheme: Scheme) -> Result<String, Error> {
+        let registry = self.repository.registry.as_ref();
+        let registry = registry.ok_or(Error::MissingRegistry)?;
+
+        let reference = match (&self.tag, &self.digest) {
+            (_, Some(digest)) => digest.to_string(),
+            (Some(tag), None) => tag.to_string(),
+            (None, None) => "latest".into(),
+        };
+
+        Ok(format!(
+            "{}{}/manifests/{}",
+            registry.api_base_url(scheme),
+            self.repository.to_registry_path(),
+            reference
+        ))
+    }
+
+    /// Rejects images with neither a tag nor a digest
+    ///
+    /// ```rust
+    /// use oci_imgref::image::{Error, Image};
+    ///
+    /// let image: Image = "ubuntu".parse().unwrap();
+    /// assert_eq!(image.ensure_tag_or_digest(), Err(Error::Underspecified));
+    /// ```
+    #[inline]
+    pub fn ensure_tag_or_digest(&self) -> Result<(), Error> {
+        match (&self.tag, &self.digest) {
+            (None, None) => Err(Error::Underspecified),
+            _ => Ok(()),
+        }
+    }
+
+    /// Returns `true` if the image names both a registry and a tag or digest
+    ///
+    /// Such a reference leaves nothing to the registry or tag defaults, so
+    /// clients can request it as is. Only presence is checked: Docker Hub
+    /// references without an organization, such as `docker.io/ubuntu:latest`,
+    /// still gain `library` in [`Image::to_canonical`].
+    ///
+    /// ```rust
+    /// use oci_imgref::image::Image;
+    ///
+    /// let image: Image = "quay.io/foo/bar:latest".parse().unwrap();
+    /// assert!(image.is_fully_qualified());
+    ///
+    /// let image: Image = "quay.io/foo/bar".parse().unwrap();
+    /// assert!(!image.is_fully_qualified());
+    /// ```
+    #[inline]
+    pub const fn is_fully_qualified(&self) -> bool {
+        self.repository.registry.is_some() && (self.tag.is_some() || self.digest.is_some())
+    }
+
+    /// Formats the image, after checking that the result parses back to it
+    ///
+    /// Since the fields are public, an image can be built that [`Display`]
+    /// renders as a string that fails to parse, or parses differently. This
+    /// validates every component as parsing would, along with ambiguities
+    /// such as an organization that reads as a registry, before formatting.
+    ///
+    /// ```rust
+    /// use oci_imgref::image::{Error, Image};
+    /// use oci_imgref::repository::Error as RepoError;
+    ///
+    /// let mut image: Image = "foo/bar:latest".parse().unwrap();
+    /// assert_eq!(image.checked_display().as_deref(), Ok("foo/bar:latest"));
+    ///
+    /// image.repository.organization = Some("my.app".into());
+    /// assert_eq!(image.checked_display(), Err(Error::Repository(RepoError::Organization)));
+    /// ```
+    pub fn checked_display(&self) -> Result<String, Error> {
+        self.check()?;
+        Ok(self.to_string())
+    }
+
+    /// Returns `true` if the image upholds every invariant parsing ensures
+    ///
+    /// This runs the same checks as [`Image::checked_display`], so a
+    /// consistent image displays as a string that parses back to it.
+    ///
+    /// ```rust
+    /// use oci_imgref::image::Image;
+    ///
+    /// let mut image: Image = "quay.io/foo/bar:latest".parse().unwrap();
+    /// assert!(image.is_consistent());
+    ///
+    /// image.tag = Some("-latest".into());
+    /// assert!(!image.is_consistent());
+    /// ```
+    #[inline]
+    pub fn is_consistent(&self) -> bool {
+        self.check().is_ok()
+    }
+
+    fn check(&self) -> Result<(), Error> {
+        crate::repository::check(&self.repository)?;
+
+        if let Some(tag) = &self.tag {
+            self::tag(tag)?;
+        }
+
+        Ok(())
+    }
+
+    /// Checks the image against the strict OCI grammar
+    ///
+    /// See [`Repository::validate_strict`] for the rules applied to the
+    /// repository. The tag is revalidated as well, which catches images
+    /// built by hand through the public fields.
+    ///
+    /// ```rust
+    /// use oci_imgref::image::{Error, Image};
+    /// use oci_imgref::repository::Error as RepoError;
+    ///
+    /// let mut image: Image = "quay.io/foo/bar:latest".parse().unwrap();
+    /// assert_eq!(image.validate_strict(), Ok(()));
+    ///
+    /// image.repository.container = "Bar".into();
+    /// assert_eq!(image.validate_strict(), Err(Error::Repository(RepoError::Container)));
+    /// ```
+    pub fn validate_strict(&self) -> Result<(), Error> {
+        self.repository.validate_strict()?;
+
+        if let Some(tag) = &self.tag {
+            self::tag(tag)?;
+        }
+
+        Ok(())
+    }
+
+    /// Returns the canonical form of the image
+    ///
+    /// The following normalizations are applied, mirroring how the Docker
+    /// CLI resolves references:
+    ///
+    /// 1. A missing registry becomes `docker.io`.
+    /// 2. Docker Hub aliases (see [`Registry::is_default`]), including any
+    ///    explicit port 443, become `docker.io`.
+    /// 3. A Docker Hub repository without an organization gains `library`.
+    /// 4. The registry host is lowercased, except for any IPv6 zone.
+    /// 5. An image with neither tag nor digest gains the `latest` tag.
+    ///
+    /// ```rust
+    /// use oci_imgref::image::Image;
+    ///
+    /// let image: Image = "ubuntu".parse().unwrap();
+    /// assert_eq!(image.to_canonical().to_string(), "docker.io/library/ubuntu:latest");
+    /// ```
+    pub fn to_canonical(&self) -> Self {
+        let mut image = self.clone();
+        let repository = &mut image.repository;
+
+        let registry = repository.registry.get_or_insert_with(Registry::docker_hub);
+        if registry.is_default() {
+            *registry = Registry::docker_hub();
+            if repository.organization.is_none() {
+                repository.organization = Some("library".into());
+            }
+        } else {
+            crate::registry::lowercase(&mut registry.host);
+        }
+
+        if image.tag.is_none() && image.digest.is_none() {
+            image.tag = Some("latest".into());
+        }
+
+        image
+    }
+
+    /// Returns the reference as `docker pull` would resolve it
+    ///
+    /// This is the [`Display`] form of [`Image::to_canonical`]: fully
+    /// qualified with a registry and always carrying a tag or digest. By
+    /// contrast, [`Display`] reproduces the reference as written, so
+    /// `ubuntu` stays `ubuntu` there.
+    ///
+    /// ```rust
+    /// use oci_imgref::image::Image;
+    ///
+    /// let image: Image = "ubuntu".parse().unwrap();
+    /// assert_eq!(image.to_string(), "ubuntu");
+    /// assert_eq!(image.to_pull_string(), "docker.io/library/ubuntu:latest");
+    /// ```
+    #[inline]
+    pub fn to_pull_string(&self) -> String {
+        self.to_canonical().to_string()
+    }
+
+    /// Returns the reference single-quoted for a POSIX shell
+    ///
+    /// Valid references never need quoting, but images whose fields were
+    /// assigned directly may hold anything. Each `'` is written as `'\''`,
+    /// so the result is always a single word to `sh`.
+    ///
+    /// ```rust
+    /// use oci_imgref::image::Image;
+    ///
+    /// let image: Image = "quay.io/foo/bar:latest".parse().unwrap();
+    /// assert_eq!(image.shell_quoted(), "'quay.io/foo/bar:latest'");
+    /// ```
+    pub fn shell_quoted(&self) -> String {
+        let mut quoted = String::with_capacity(self.len() + 2);
+
+        quoted.push('\'');
+        for c in self.to_string().chars() {
+            match c {
+                '\'' => quoted.push_str(r"'\''"),
+                c => quoted.push(c),
+            }
+        }
+        quoted.push('\'');
+
+        quoted
+    }
+
+    /// Returns `true` if both images are equal once canonicalized
+    ///
+    /// See [`Image::to_canonical`] for the normalizations applied.
+    ///
+    /// ```rust
+    /// use oci_imgref::image::Image;
+    ///
+    /// let a: Image = "ubuntu".parse().unwrap();
+    /// let b: Image = "docker.io/library/ubuntu:latest".parse().unwrap();
+    /// assert!(a.canonical_eq(&b));
+    /// ```
+    #[inline]
+    pub fn canonical_eq(&self, other: &Self) -> bool {
+        self.to_canonical() == other.to_canonical()
+    }
+
+    /// Returns `true` if both images could name the same content
+    ///
+    /// The repositories must be equal once canonicalized (see
+    /// [`Image::canonical_eq`]). Then two digests decide alone. If only one
+    /// image has a digest, the tags are ignored: which digest a tag points to
+    /// can only be learned from the registry, and may change over time.
+    /// Without digests, two tags must be equal. A `true` result is therefore
+    /// only a hint that a pull might be redundant, while `false` is certain.
+    ///
+    /// ```rust
+    /// use oci_imgref::image::Image;
+    ///
+    /// let digest = "sha256:e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855";
+    /// let a: Image = "ubuntu:22.04".parse().unwrap();
+    /// let b: Image = format!("docker.io/library/ubuntu@{digest}").parse().unwrap();
+    /// let c: Image = "ubuntu:24.04".parse().unwrap();
+    /// assert!(a.may_be_same_content(&b));
+    /// assert!(!a.may_be_same_content(&c));
+    /// ```
+    pub fn may_be_same_content(&self, other: &Self) -> bool {
+        let a = self.to_canonical();
+        let b = other.to_canonical();
+
+        if a.repository != b.repository {
+            return false;
+        }
+
+        match (&a.digest, &b.digest) {
+            (Some(x), Some(y)) => x == y,
+            (None, None) => a.tag == b.tag,
+            _ => true,
+        }
+    }
+
+    /// Returns the registry host and port, if the image names a registry
+    ///
+    /// ```rust
+    /// use oci_imgref::image::Image;
+    ///
+    /// let image: Image = "localhost:5000/foo".parse().unwrap();
+    /// let (host, port) = image.registry_and_port().unwrap();
+    /// assert_eq!(host, "localhost");
+    /// assert_eq!(port.unwrap().get(), 5000);
+    /// ```
+    #[inline]
+    pub fn registry_and_port(&self) -> Option<(&str, Option<NonZeroU16>)> {
+        let registry = self.repository.registry.as_ref()?;
+        Some((&registry.host, registry.port))
+    }
+
+    /// Iterates over the components of the image in display order
+    ///
+    /// ```rust
+    /// use oci_imgref::image::{Component, Image};
+    ///
+    /// let image: Image = "foo/bar:latest".parse().unwrap();
+    /// let components: Vec<_> = image.components().collect();
+    /// assert_eq!(
+    ///     components,
+    ///     [
+    ///         Component::Organization("foo"),
+    ///         Component::Container("bar"),
+    ///         Component::Tag("latest"),
+    ///     ]
+    /// );
+    /// ```
+    pub fn components(&self) -> impl Iterator<Item = Component<'_>> {
+        let repository = &self.repository;
+
+        let registry = repository.registry.as_ref().map(Component::Registry);
+        let organization = repository.organization.as_deref();
+        let container = Component::Container(&repository.container);
+
+        registry
+            .into_iter()
+            .chain(organization.map(Component::Organization))
+            .chain(core::iter::once(container))
+            .chain(self.tag.as_deref().map(Component::Tag))
+            .chain(self.digest.as_ref().map(Component::Digest))
+    }
+
+    /// Returns the repository
+    ///
+    /// This is the same as the public field, for symmetry with the other
+    /// accessors.
+    #[inline]
+    pub fn repository(&self) -> &Repository {
+        &self.repository
+    }
+
+    /// Returns the repository, discarding the tag and digest
+    ///
+    /// Use [`AsRef<Repository>`](#impl-AsRef<Repository>-for-Image) to
+    /// borrow it instead.
+    ///
+    /// ```rust
+    /// use oci_imgref::image::Image;
+    ///
+    /// let image: Image = "quay.io/foo/bar:latest".parse().unwrap();
+    /// assert_eq!(image.into_repository().to_string(), "quay.io/foo/bar");
+    /// ```
+    #[inline]
+    pub fn into_repository(self) -> Repository {
+        self.repository
+    }
+
+    /// Pins the image to a resolved digest
+    ///
+    /// The tag, if any, is kept for readability, yielding the
+    /// `name:tag@digest` form. Any previous digest is replaced.
+    ///
+    /// ```rust
+    /// use oci_imgref::image::Image;
+    ///
+    /// let image: Image = "quay.io/foo/bar:latest".parse().unwrap();
+    /// let digest = "sha256:e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855";
+    /// let image = image.pin(digest.parse().unwrap());
+    /// assert_eq!(image.to_string(), format!("quay.io/foo/bar:latest@{digest}"));
+    /// ```
+    #[inline]
+    pub fn pin(self, digest: Digest) -> Self {
+        Self {
+            digest: Some(digest),
+            ..self
+        }
+    }
+
+    /// Replaces the registry if it matches `from`
+    ///
+    /// Images whose registry does not match, or that name no registry at
+    /// all, are returned unchanged. Parse with
+    /// [`default_docker_hub`](ParseOptions::default_docker_hub) to make
+    /// an implied Docker Hub registry explicit first.
+    ///
+    /// ```rust
+    /// use oci_imgref::image::Image;
+    /// use oci_imgref::registry::{Registry, RegistryPattern};
+    ///
+    /// let from: RegistryPattern = "docker.io".parse()?;
+    /// let to: Registry = "mirror.example.com".parse()?;
+    ///
+    /// let image: Image = "docker.io/library/ubuntu:latest".parse().unwrap();
+    /// let image = image.rewrite_registry(&from, &to);
+    /// assert_eq!(image.to_string(), "mirror.example.com/library/ubuntu:latest");
+    /// # Ok::<(), oci_imgref::registry::Error>(())
+    /// ```
+    pub fn rewrite_registry(mut self, from: &RegistryPattern, to: &Registry) -> Self {
+        if let Some(registry) = &mut self.repository.registry {
+            if from.matches(registry) {
+                registry.clone_from(to);
+            }
+        }
+
+        self
+    }
+
+    /// Writes the reference to any [`core::fmt::Write`]
+    ///
+    /// This renders exactly like [`Display`], but lets `no_std` callers
+    /// without an allocator format into a fixed-capacity buffer such as a
+    /// `heapless::String`.
+    ///
+    /// ```rust
+    /// use oci_imgref::image::Image;
+    ///
+    /// let image: Image = "quay.io/foo/bar:latest".parse().unwrap();
+    ///
+    /// let mut buffer = heapless::String::<64>::new();
+    /// image.write_to(&mut buffer).unwrap();
+    /// assert_eq!(buffer, "quay.io/foo/bar:latest");
+    /// ```
+    pub fn write_to<W: core::fmt::Write>(&self, w: &mut W) -> core::fmt::Result {
+        write!(w, "{}", self.repository)?;
+
+        if let Some(tag) = &self.tag {
+            write!(w, ":{}", tag)?;
+        }
+
+        if let Some(digest) = &self.digest {
+            write!(w, "@{}", digest)?;
+        }
+
+        Ok(())
+    }
+
+    /// Returns the length of the reference as written by [`Display`]
+    ///
+    /// The length is summed from the components rather than by formatting
+    /// the whole reference; only the digest is measured through its
+    /// [`Display`], without allocating.
+    ///
+    /// ```rust
+    /// use oci_imgref::image::Image;
+    ///
+    /// let image: Image = "quay.io:5000/foo/bar:latest".parse().unwrap();
+    /// assert_eq!(image.len(), "quay.io:5000/foo/bar:latest".len());
+    /// ```
+    #[allow(clippy::len_without_is_empty)] // references are never empty
+    pub fn len(&self) -> usize {
+        let tag = self.tag.as_ref().map_or(0, |tag| tag.len() + 1);
+        let digest = self.digest.as_ref().map_or(0, |d| Measure::of(d) + 1);
+        self.repository.len() + tag + digest
+    }
+
+    /// Starts building an image of `repository`
+    ///
+    /// ```rust
+    /// use oci_imgref::image::Image;
+    ///
+    /// let repository = "quay.io/foo/bar".parse()?;
+    /// let digest = "sha256:e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855";
+    ///
+    /// // Typed components cannot fail to build.
+    /// let image: Image = Image::builder(repository).digest(digest.parse()?).build();
+    /// assert_eq!(image.to_string(), format!("quay.io/foo/bar@{digest}"));
+    ///
+    /// // Strings are validated, so building may fail.
+    /// let image = Image::builder(image.repository).tag("latest").build()?;
+    /// assert_eq!(image.to_string(), "quay.io/foo/bar:latest");
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub const fn builder(repository: Repository) -> ImageBuilder {
+        ImageBuilder {
+            image: Self {
+                repository,
+                tag: None,
+                digest: None,
+            },
+            error: None,
+            state: PhantomData,
+        }
+    }
+
+    /// Returns the tag and digest of the image
+    ///
+    /// ```rust
+    /// use oci_imgref::image::{Image, Reference};
+    ///
+    /// let image: Image = "quay.io/foo/bar:latest".parse().unwrap();
+    /// assert_eq!(image.reference(), Reference::Tag("latest".into()));
+    /// ```
+    pub fn reference(&self) -> Reference {
+        match (self.tag.as_deref().map(String::from), self.digest.clone()) {
+            (None, None) => Reference::None,
+            (Some(tag), None) => Reference::Tag(tag),
+            (None, Some(digest)) => Reference::Digest(digest),
+            (Some(tag), Some(digest)) => Reference::Both(tag, digest),
+        }
+    }
+
+    /// Stages changes to the tag and digest, applied together on commit
+    ///
+    /// ```rust
+    /// use oci_imgref::image::{Image, Reference};
+    ///
+    /// let mut image: Image = "quay.io/foo/bar:latest".parse()?;
+    /// let digest = "sha256:e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855";
+    ///
+    /// let mut reference = image.reference_mut();
+    /// reference.tag(None).digest(Some(digest.parse()?));
+    /// reference.commit()?;
+    ///
+    /// assert_eq!(image.to_string(), format!("quay.io/foo/bar@{digest}"));
+    /// # Ok::<(), oci_imgref::image::Error>(())
+    /// ```
+    pub fn reference_mut(&mut self) -> ReferenceMut<'_> {
+        ReferenceMut {
+            tag: self.tag.as_deref().map(String::from),
+            digest: self.digest.clone(),
+            image: self,
+        }
+    }
+
+    /// Replaces the tag after validating it
+    ///
+    /// On error the image is left unchanged.
+    ///
+    /// ```rust
+    /// use oci_imgref::image::{Error, Image};
+    ///
+    /// let mut image: Image = "quay.io/foo/bar:latest".parse()?;
+    /// image.set_tag("v1.2")?;
+    /// assert_eq!(image.to_string(), "quay.io/foo/bar:v1.2");
+    ///
+    /// assert_eq!(image.set_tag(".v1"), Err(Error::Tag));
+    /// assert_eq!(image.tag.as_deref(), Some("v1.2"));
+    /// # Ok::<(), oci_imgref::image::Error>(())
+    /// ```
+    pub fn set_tag(&mut self, tag: &str) -> Result<(), Error> {
+        self::tag(tag)?;
+        self.tag = Some(Cow::Owned(tag.into()));
+        Ok(())
+    }
+
+    /// Replaces the digest after parsing it
+    ///
+    /// On error the image is left unchanged.
+    pub fn set_digest(&mut self, digest: &str) -> Result<(), Error> {
+        self.digest = Some(digest.parse()?);
+        Ok(())
+    }
+}
+
+/// Borrows the repository of an image
+///
+/// There is deliberately no `Borrow<Repository>` impl: `Borrow` requires
+/// the borrowed form to compare and hash like the owner, but two images of
+/// the same repository differ in their tag and digest. To look up an image
+/// in a map keyed by repository, borrow the repository instead.
+///
+/// ```rust
+/// use std::collections::HashMap;
+/// use oci_imgref::image::Image;
+///
+/// let mut pulls = HashMap::new();
+/// let image: Image = "quay.io/foo/bar:latest".parse().unwrap();
+/// pulls.insert(image.repository.clone(), 1);
+///
+/// let image: Image = "quay.io/foo/bar:v2".parse().unwrap();
+/// assert_eq!(pulls.get(image.as_ref()), Some(&1));
+/// ```
+impl AsRef<Repository> for Image {
+    #[inline(always)]
+    fn as_ref(&self) -> &Repository {
+        &self.repository
+    }
+}
+
+/// Orders images by repository, then tag, then digest
+///
+/// Repositories compare by registry, organization and container, in that
+/// order. A missing registry, organization, tag or digest sorts before any
+/// present one. Digests compare by algorithm, then by encoded value, so
+/// references differing only by digest still sort deterministically.
+///
+/// ```rust
+/// use oci_imgref::image::Image;
+///
+/// let mut images: Vec<Image> = ["quay.io/foo/bar:v2", "foo/bar", "quay.io/foo/bar:v1"]
+///     .iter()
+///     .map(|s| s.parse().unwrap())
+///     .collect();
+///
+/// images.sort();
+/// assert_eq!(images[0].to_string(), "foo/bar");
+/// assert_eq!(images[1].to_string(), "quay.io/foo/bar:v1");
+/// ```
+impl Ord for Image {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.repository
+            .cmp(&other.repository)
+            .then_with(|| self.tag.cmp(&other.tag))
+            .then_with(|| match (&self.digest, &other.digest) {
+                (Some(a), Some(b)) => cmp_digests(a, b),
+                (a, b) => a.is_some().cmp(&b.is_some()),
+            })
+    }
+}
+
+/// Compares digests by algorithm, then by hex
+///
+/// Digests too long to be formatted on the stack, which no supported
+/// algorithm produces, are compared through allocated strings instead.
+fn cmp_digests(a: &Digest, b: &Digest) -> Ordering {
+    match (DigestBuf::of(a), DigestBuf::of(b)) {
+        (Some(a), Some(b)) => a.parts().cmp(&b.parts()),
+        _ => {
+            let (a, b) = (a.to_string(), b.to_string());
+            a.split_once(':').cmp(&b.split_once(':'))
+        }
+    }
+}
+
+impl PartialOrd for Image {
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl From<Image> for String {
+    fn from(value: Image) -> Self {
+        let mut out = String::with_capacity(value.len());
+        let _ = value.write_to(&mut out);
+        out
+    }
+}
+
+/// counts the bytes of a formatted value without allocating
+struct Measure(usize);
+
+impl Measure {
+    fn of(value: impl Display) -> usize {
+        use core::fmt::Write;
+
+        let mut measure = Self(0);
+        let _ = write!(measure, "{}", value);
+        measure.0
+    }
+}
+
+impl core::fmt::Write for Measure {
+    fn write_str(&mut self, s: &str) -> core::fmt::Result {
+        self.0 += s.len();
+        Ok(())
+    }
+}
+
+/// a digest formatted on the stack
+///
+/// A [`Digest`] can only be read through [`Display`], so this formats it
+/// into a buffer large enough for every supported algorithm.
+struct DigestBuf {
+    buf: [u8; DigestBuf::CAPACITY],
+    len: usize,
+}
+
+impl DigestBuf {
+    /// the length of a SHA-512 digest, the longest supported
+    const CAPACITY: usize = "sha512:".len() + Algorithm::Sha512.hex_len();
+
+    /// Formats `digest`, or returns `None` if it does not fit
+    fn of(digest: &Digest) -> Option<Self> {
+        use core::fmt::Write;
+
+        let mut buf = Self {
+            buf: [0; Self::CAPACITY],
+            len: 0,
+        };
+
+        write!(buf, "{}", digest).ok()?;
+        Some(buf)
+    }
+
+    /// Returns the algorithm and the encoded hash
+    fn parts(&self) -> (&[u8], &[u8]) {
+        let bytes = &self.buf[..self.len];
+        match bytes.iter().position(|&b| b == b':') {
+            Some(colon) => (&bytes[..colon], &bytes[colon + 1..]),
+            None => (bytes, &[]),
+        }
+    }
+}
+
+impl core::fmt::Write for DigestBuf {
+    fn write_str(&mut self, s: &str) -> core::fmt::Result {
+        let end = self.len + s.len();
+        let buf = self.buf.get_mut(self.len..end).ok_or(core::fmt::Error)?;
+        buf.copy_from_slice(s.as_bytes());
+        self.len = end;
+        Ok(())
+    }
+}
+
+impl Display for Image {
+    #[inline(always)]
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        self.write_to(f)
+    }
+}
+
+impl FromStr for Image {
+    type Err = Error;
+
+    fn from_str(from: &str) -> Result<Self, Self::Err> {
+        Ok(split(from)?.into_image())
+    }
+}
+
+impl TryFrom<String> for Image {
+    type Error = Error;
+
+    #[inline(always)]
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl TryFrom<&str> for Image {
+    type Error = Error;
+
+    #[inline(always)]
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl TryFrom<&[u8]> for Image {
+    type Error = Error;
+
+    #[inline(always)]
+    fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
+        core::str::from_utf8(value).or(Err(Error::Utf8))?.parse()
+    }
+}
+
+/// the byte ranges of the components of an image reference
+///
+/// This is produced by [`parse_ranges`] and borrows nothing, so it can be
+/// kept around and applied to the original input later.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ImageRanges {
+    registry: Option<(usize, usize)>,
+    organization: Option<(usize, usize)>,
+    container: (usize, usize),
+    tag: Option<(usize, usize)>,
+    digest: Option<(usize, usize)>,
+}
+
+impl ImageRanges {
+    /// the range of the registry, including any port
+    #[inline]
+    pub fn registry(&self) -> Option<Range<usize>> {
+        self.registry.map(|(start, end)| start..end)
+    }
+
+    /// the range of the organization
+    #[inline]
+    pub fn organization(&self) -> Option<Range<usize>> {
+        self.organization.map(|(start, end)| start..end)
+    }
+
+    /// the range of the container
+    #[inline]
+    pub fn container(&self) -> Range<usize> {
+        self.container.0..self.container.1
+    }
+
+    /// the range of the path after the registry, i.e. the organization and
+    /// container, excluding the `/` that separates it from the registry
+    ///
+    /// Together with [`ImageRanges::registry`], this marks where the registry
+    /// ends and the path begins.
+    ///
+    /// ```rust
+    /// use oci_imgref::image::parse_ranges;
+    ///
+    /// let input = "quay.io:5000/foo/bar:latest";
+    /// let ranges = parse_ranges(input)?;
+    /// assert_eq!(&input[ranges.registry().unwrap()], "quay.io:5000");
+    /// assert_eq!(&input[ranges.path()], "foo/bar");
+    /// # Ok::<(), oci_imgref::image::Error>(())
+    /// ```
+    #[inline]
+    pub fn path(&self) -> Range<usize> {
+        let start = self
+            .organization
+            .map_or(self.container.0, |(start, _)| start);
+        start..self.container.1
+    }
+
+    /// the range of the tag, excluding the `:`
+    #[inline]
+    pub fn tag(&self) -> Option<Range<usize>> {
+        self.tag.map(|(start, end)| start..end)
+    }
+
+    /// the range of the digest, excluding the `@`
+    #[inline]
+    pub fn digest(&self) -> Option<Range<usize>> {
+        self.digest.map(|(start, end)| start..end)
+    }
+}
+
+/// Validates an image reference and locates its components
+///
+/// This performs the same validation as parsing an [`Image`] but keeps no
+/// copies of the components, so no allocation is made. A digest is checked
+/// against the grammar [`Digest`] parses in place.
+///
+/// ```rust
+/// use oci_imgref::image::parse_ranges;
+///
+/// let input = "quay.io/foo/bar:latest";
+/// let ranges = parse_ranges(input)?;
+/// assert_eq!(&input[ranges.registry().unwrap()], "quay.io");
+/// assert_eq!(&input[ranges.organization().unwrap()], "foo");
+/// assert_eq!(&input[ranges.container()], "bar");
+/// assert_eq!(&input[ranges.tag().unwrap()], "latest");
+/// assert!(ranges.digest().is_none());
+/// # Ok::<(), oci_imgref::image::Error>(())
+/// ```
+pub fn parse_ranges(input: &str) -> Result<ImageRanges, Error> {
+    Ok(split_with(input, check_digest)?.ranges(input))
+}
+
+/// an image parsed together with the ranges of its components
+///
+/// This is produced by [`parse`]. The ranges index into the parsed input.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Parsed {
+    /// the parsed image
+    pub image: Image,
+
+    /// the byte ranges of the components within the input
+    pub ranges: ImageRanges,
+}
+
+/// Parses an image reference and locates its components in one call
+///
+/// This is equivalent to calling both [`str::parse`] and [`parse_ranges`],
+/// but only parses the input once.
+///
+/// ```rust
+/// let input = "quay.io/foo/bar:latest";
+/// let parsed = oci_imgref::parse(input)?;
+/// assert_eq!(parsed.image.tag.as_deref(), Some("latest"));
+/// assert_eq!(&input[parsed.ranges.tag().unwrap()], "latest");
+/// # Ok::<(), oci_imgref::image::Error>(())
+/// ```
+pub fn parse(input: &str) -> Result<Parsed, Error> {
+    let split = split(input)?;
+
+    Ok(Parsed {
+        ranges: split.ranges(input),
+        image: split.into_image(),
+    })
+}
+
+/// an error located within the input
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ErrorAt {
+    /// the error
+    pub error: Error,
+
+    /// the byte range of the offending component
+    pub range: Range<usize>,
+}
+
+/// Parses an image reference, reporting every invalid component
+///
+/// Where [`str::parse`] stops at the first error, this validates each
+/// component independently and reports all errors in input order. If the
+/// repository is valid, the image is still returned with any invalid tag
+/// or digest left out. Whitespace and illegal characters make the
+/// boundaries unreliable, so they are reported on their own.
+///
+/// ```rust
+/// use oci_imgref::image::{parse_recoverable, Error};
+///
+/// let (image, errors) = parse_recoverable("quay.io/foo:-bar");
+/// assert_eq!(image.unwrap().to_string(), "quay.io/foo");
+/// assert_eq!(errors[0].error, Error::Tag);
+/// assert_eq!(errors[0].range, 12..16);
+/// ```
+pub fn parse_recoverable(input: &str) -> (Option<Image>, Vec<ErrorAt>) {
+    if let Ok(split) = split(input) {
+        return (Some(split.into_image()), Vec::new());
+    }
+
+    let bounds = locate(input);
+    let mut errors = Vec::new();
+
+    if let Some(i) = bounds.whitespace {
+        let len = input[i..].chars().next().map_or(1, char::len_utf8);
+        errors.push(ErrorAt {
+            error: Error::Whitespace,
+            range: i..i + len,
+        });
+
+        return (None, errors);
+    }
+
+    if let Some((i, found)) = bounds.illegal {
+        let len = input[i..].chars().next().map_or(1, char::len_utf8);
+        errors.push(ErrorAt {
+            error: Error::IllegalCharacter { found },
+            range: i..i + len,
+        });
+
+        return (None, errors);
+    }
+
+    let range = bounds.repository();
+    let repository = &input[range.clone()];
+    let failures = crate::repository::check_all(repository, bounds.slashes);
+    let repository = match failures.is_empty() {
+        true => crate::repository::split_at(repository, bounds.slashes).ok(),
+        false => None,
+    };
+
+    errors.extend(failures.into_iter().map(|(error, range)| ErrorAt {
+        error: error.into(),
+        range,
+    }));
+
+    let tag = bounds
+        .tag()
+        .and_then(|range| match tag(&input[range.clone()]) {
+            Ok(()) => Some(&input[range]),
+            Err(error) => {
+                errors.push(ErrorAt { error, range });
+                None
+            }
+        });
+
+    let digest = bounds
+        .digest()
+        .and_then(|range| match input[range.clone()].parse::<Digest>() {
+            Ok(digest) => Some((&input[range], digest)),
+            Err(error) => {
+                errors.push(ErrorAt {
+                    error: error.into(),
+                    range,
+                });
+                None
+            }
+        });
+
+    let image = repository.map(|parts| {
+        Split {
+            repository: &input[range],
+            parts,
+            tag,
+            digest,
+        }
+        .into_image()
+    });
+
+    (image, errors)
+}
+
+/// Parses a newline-separated list of image references
+///
+/// Each line is trimmed, and blank lines and lines starting with `#` are
+/// skipped. On failure, the zero-based index of the offending line is
+/// returned with its error.
+///
+/// ```rust
+/// use oci_imgref::image::{parse_list, Error};
+///
+/// let images = parse_list("# base images\nubuntu:22.04\n\nquay.io/foo/bar\n")?;
+/// assert_eq!(images.len(), 2);
+///
+/// assert_eq!(parse_list("ubuntu\nfoo:\n"), Err((1, Error::Tag)));
+/// # Ok::<(), (usize, Error)>(())
+/// ```
+pub fn parse_list(input: &str) -> Result<Vec<Image>, (usize, Error)> {
+    input
+        .lines()
+        .map(str::trim)
+        .enumerate()
+        .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'))
+        .map(|(index, line)| line.parse().map_err(|error| (index, error)))
+        .collect()
+}
+
+/// Canonicalizes a list of images and removes duplicates
+///
+/// Each image is replaced by its [`Image::to_canonical`] form, and only the
+/// first of any images that are [`Image::canonical_eq`] is kept, preserving
+/// the order of the input.
+///
+/// ```rust
+/// use oci_imgref::image::{normalize_images, Image};
+///
+/// let images: Vec<Image> = ["ubuntu", "docker.io/library/ubuntu:latest", "quay.io/foo/bar"]
+///     .iter()
+///     .map(|s| s.parse().unwrap())
+///     .collect();
+///
+/// let images = normalize_images(&images);
+/// assert_eq!(images.len(), 2);
+/// assert_eq!(images[0].to_string(), "docker.io/library/ubuntu:latest");
+/// assert_eq!(images[1].to_string(), "quay.io/foo/bar:latest");
+/// ```
+pub fn normalize_images(images: &[Image]) -> Vec<Image> {
+    let mut seen = BTreeSet::new();
+
+    images
+        .iter()
+        .map(Image::to_canonical)
+        .filter(|image| seen.insert(image.to_string()))
+        .collect()
+}
+
+/// Rewrites the registry of each image by the first matching rule
+///
+/// Each rule maps a [`RegistryPattern`] to the registry replacing it, as in
+/// a mirror configuration. Rules are tried in order and at most one is
+/// applied per image, so a rewritten registry never triggers a later rule.
+/// Images matching no rule are left unchanged.
+///
+/// ```rust
+/// use oci_imgref::image::{rewrite_all, Image};
+///
+/// let rules = [
+///     ("docker.io".parse()?, "mirror.example.com".parse()?),
+///     ("*.example.com".parse()?, "cache.example.com".parse()?),
+/// ];
+///
+/// let mut images = ["docker.io/library/ubuntu".parse::<Image>().unwrap()];
+/// rewrite_all(&mut images, &rules);
+/// assert_eq!(images[0].to_string(), "mirror.example.com/library/ubuntu");
+/// # Ok::<(), oci_imgref::registry::Error>(())
+/// ```
+pub fn rewrite_all(images: &mut [Image], rules: &[(RegistryPattern, Registry)]) {
+    for registry in images
+        .iter_mut()
+        .filter_map(|i| i.repository.registry.as_mut())
+    {
+        if let Some((_, to)) = rules.iter().find(|(from, _)| from.matches(registry)) {
+            registry.clone_from(to);
+        }
+    }
+}
+
+/// Builds the HTTPS manifest URL of an image
+///
+/// See [`Image::manifest_url`] for the URL produced. Images without a
+/// registry are rejected with [`Error::MissingRegistry`].
+///
+/// ```rust
+/// use oci_imgref::image::Image;
+///
+/// let image: Image = "quay.io/foo/bar:latest".parse()?;
+/// let uri = http::Uri::try_from(&image)?;
+/// assert_eq!(uri.host(), Some("quay.io"));
+/// assert_eq!(uri.path(), "/v2/foo/bar/manifests/latest");
+/// # Ok::<(), oci_imgref::image::Error>(())
+/// ```
+#[cfg(feature = "http")]
+impl TryFrom<&Image> for http::Uri {
+    type Error = Error;
+
+    fn try_from(image: &Image) -> Result<Self, Self::Error> {
+        let url = image.manifest_url(Scheme::Https)?;
+        Self::try_from(url).or(Err(Error::Uri))
+    }
+}
+
+/// Maps a registry URL to an image
+///
+/// The URL maps onto the image as follows:
+///
+/// - the scheme must be `http` or `https`; neither is stored
+/// - the host and port become the registry; a port that is the default
+///   for the scheme is dropped by the URL parser
+/// - the path becomes the repository, with every segment validated
+/// - the fragment, if any, becomes the digest if it contains a `:` and
+///   the tag otherwise
+///
+/// URLs with credentials or a query, or that fail validation, are rejected
+/// with [`Error::Url`] or the error of the offending component.
+///
+/// ```rust
+/// use oci_imgref::image::Image;
+///
+/// let url = url::Url::parse("https://quay.io:5000/foo/bar#latest").unwrap();
+/// let image = Image::try_from(&url)?;
+/// assert_eq!(image.to_string(), "quay.io:5000/foo/bar:latest");
+/// # Ok::<(), oci_imgref::image::Error>(())
+/// ```
+#[cfg(feature = "url")]
+impl TryFrom<&url::Url> for Image {
+    type Error = Error;
+
+    fn try_from(url: &url::Url) -> Result<Self, Self::Error> {
+        let plain = url.username().is_empty() && url.password().is_none();
+        if !matches!(url.scheme(), "http" | "https") || !plain || url.query().is_some() {
+            return Err(Error::Url);
+        }
+
+        let host = url.host_str().ok_or(Error::Url)?;
+        let port = url.port().map(NonZeroU16::new).map(|p| p.ok_or(Error::Url));
+        let registry = Registry {
+            host: Cow::Owned(host.into()),
+            port: port.transpose()?,
+        };
+
+        crate::registry::validate(&registry.host).map_err(|e| Error::Repository(e.into()))?;
+
+        let path = url.path().strip_prefix('/').ok_or(Error::Url)?;
+        let repository = Repository::from_segments(Some(registry), path.split('/'))?;
+        let image = Self::builder(repository);
+
+        match url.fragment() {
+            None => Ok(image.build()),
+            Some(reference) if reference.contains(':') => image.digest_str(reference).build(),
+            Some(reference) => image.tag(reference).build(),
+        }
+    }
+}
+
+#[cfg(feature = "url")]
+impl TryFrom<url::Url> for Image {
+    type Error = Error;
+
+    #[inline(always)]
+    fn try_from(url: url::Url) -> Result<Self, Self::Error> {
+        Self::try_from(&url)
+    }
+}
+
+/// Serializes an image as its string form, or as its parts when compact
+///
+/// No normalization is applied in either direction, so a deserialized image
+/// equals and hashes like the original. Canonicalize explicitly, with
+/// [`Image::to_canonical`], before serializing if keys should merge.
+#[cfg(feature = "serde")]
+impl serde::Serialize for Image {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            serializer.collect_str(self)
+        } else {
+            (&self.repository, &self.tag, &self.digest).serialize(serializer)
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Image {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        use serde::de::Error as _;
+
+        if deserializer.is_human_readable() {
+            let value = String::deserialize(deserializer)?;
+            value.parse().map_err(D::Error::custom)
+        } else {
+            let (repository, tag, digest) =
+                <(Repository, Option<String>, Option<Digest>)>::deserialize(deserializer)?;
+
+            let image = Self {
+                repository,
+                tag: tag.map(Cow::Owned),
+                digest,
+            };
+
+            image.check().map_err(D::Error::custom)?;
+            Ok(image)
+        }
+    }
+}
+
+/// A struct form of [`Image`] for use with `#[serde(flatten)]`
+///
+/// The default representation of an image is a single string, which cannot
+/// be flattened into a parent. Using this module with `#[serde(with)]`
+/// stores the image as the fields below instead, with `None` fields left
+/// out:
+///
+/// - `registry`: the registry and port, i.e. `quay.io:5000`
+/// - `repository`: the path without the registry, i.e. `foo/bar`
+/// - `tag`: the tag, i.e. `latest`
+/// - `digest`: the digest, i.e. `sha256:e3b0...`
+///
+/// Every field is validated when deserializing.
+///
+/// ```rust
+/// use oci_imgref::image::Image;
+///
+/// #[derive(serde::Serialize, serde::Deserialize)]
+/// struct Deployment {
+///     name: String,
+///
+///     #[serde(flatten, with = "oci_imgref::image::fields")]
+///     image: Image,
+/// }
+///
+/// let deployment = Deployment {
+///     name: "web".into(),
+///     image: "quay.io/foo/bar:latest".parse().unwrap(),
+/// };
+///
+/// let json = serde_json::to_string(&deployment).unwrap();
+/// assert_eq!(
+///     json,
+///     r#"{"name":"web","registry":"quay.io","repository":"foo/bar","tag":"latest"}"#
+/// );
+/// ```
+#[cfg(feature = "serde")]
+pub mod fields {
+    use alloc::string::{String, ToString};
+
+    use serde::de::Error as _;
+    use serde::{Deserialize, Serialize};
+
+    use super::Image;
+    use crate::repository::Repository;
+
+    #[derive(Serialize)]
+    struct Ref<'a> {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        registry: Option<String>,
+        repository: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        tag: Option<&'a str>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        digest: Option<String>,
+    }
+
+    #[derive(Deserialize)]
+    struct Owned {
+        #[serde(default)]
+        registry: Option<String>,
+        repository: String,
+        #[serde(default)]
+        tag: Option<String>,
+        #[serde(default)]
+        digest: Option<String>,
+    }
+
+    /// Serializes an image as separate fields
+    pub fn serialize<S: serde::Serializer>(
+        image: &Image,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        let repo = &image.repository;
+
+        Ref {
+            registry: repo.registry.as_ref().map(ToString::to_string),
+            repository: repo.to_registry_path(),
+            tag: image.tag.as_deref(),
+            digest: image.digest.as_ref().map(ToString::to_string),
+        }
+        .serialize(serializer)
+    }
+
+    /// Deserializes an image from separate fields
+    pub fn deserialize<'de, D: serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Image, D::Error> {
+        let fields = Owned::deserialize(deserializer)?;
+
+        let registry = fields.registry.map(|r| r.parse()).transpose();
+        let registry = registry.map_err(D::Error::custom)?;
+
+        let segments = fields.repository.split('/');
+        let repository = Repository::from_segments(registry, segments);
+        let mut image = Image {
+            repository: repository.map_err(D::Error::custom)?,
+            tag: None,
+            digest: None,
+        };
+
+        if let Some(tag) = &fields.tag {
+            image.set_tag(tag).map_err(D::Error::custom)?;
+        }
+
+        if let Some(digest) = &fields.digest {
+            image.set_digest(digest).map_err(D::Error::custom)?;
+        }
+
+        Ok(image)
+    }
+}
+
+/// an image reference split into its validated components
+///
+/// Alongside its text, the digest is kept as `D`: a parsed [`Digest`], or
+/// nothing when it was only checked.
+struct Split<'a, D = Digest> {
+    repository: &'a str,
+    parts: crate::repository::Parts<'a>,
+    tag: Option<&'a str>,
+    digest: Option<(&'a str, D)>,
+}
+
+impl<D> Split<'_, D> {
+    /// Locates the components within `input`, the string that was split
+    fn ranges(&self, input: &str) -> ImageRanges {
+        let end = self.repository.len();
+        let container = (end - self.parts.container.len(), end);
+        let organization = self
+            .parts
+            .organization
+            .map(|org| (container.0 - 1 - org.len(), container.0 - 1));
+        let registry = self
+            .parts
+            .registry
+            .map(|_| (0, organization.map_or(container.0, |org| org.0) - 1));
+
+        ImageRanges {
+            registry,
+            organization,
+            container,
+            tag: self.tag.map(|tag| (end + 1, end + 1 + tag.len())),
+            digest: self
+                .digest
+                .as_ref()
+                .map(|(digest, _)| (input.len() - digest.len(), input.len())),
+        }
+    }
+}
+
+impl Split<'_> {
+    /// Copies the components into an owned image
+    fn into_image(self) -> Image {
+        let parts = self.parts;
+
+        Image {
+            repository: Repository {
+                registry: parts.registry.map(|(host, port)| Registry {
+                    host: Cow::Owned(host.into()),
+                    port,
+                }),
+                organization: parts.organization.map(|org| Cow::Owned(org.into())),
+                container: Cow::Owned(parts.container.into()),
+            },
+            tag: self.tag.map(|tag| Cow::Owned(tag.into())),
+            digest: self.digest.map(|(_, digest)| digest),
+        }
+    }
+}
+
+/// the boundaries of an image reference, located but not validated
+struct Bounds {
+    len: usize,
+    slashes: Slashes,
+
+    // the last `@`, and the last `:` before it not followed by a `/`
+    at: Option<usize>,
+    colon: Option<usize>,
+
+    // whether an uppercase letter follows the last `@`
+    upper: bool,
+
+    // the first whitespace character
+    whitespace: Option<usize>,
+
+    // the first control or non-ASCII character that is not whitespace
+    illegal: Option<(usize, CharClass)>,
+}
+
+impl Bounds {
+    /// the range of the repository
+    fn repository(&self) -> Range<usize> {
+        0..self.colon.or(self.at).unwrap_or(self.len)
+    }
+
+    /// the range of the tag, excluding the `:`
+    fn tag(&self) -> Option<Range<usize>> {
+        let colon = self.colon?;
+        Some(colon + 1..self.at.unwrap_or(self.len))
+    }
+
+    /// the range of the digest, excluding the `@`
+    fn digest(&self) -> Option<Range<usize>> {
+        Some(self.at? + 1..self.len)
+    }
+}
+
+/// Locates the components of an image reference in a single pass
+fn locate(from: &str) -> Bounds {
+    let mut slashes = Slashes::default();
+    let mut colon = None;
+    let mut upper = false;
+    let mut ascii = true;
+    let mut control = false;
+    let mut at = None;
+    let mut whitespace = None;
+
+    for (i, b) in from.bytes().enumerate() {
+        match b {
+            b'@' => {
+                at = Some((i, slashes, colon));
+                upper = false;
+            }
+
+            b'/' => colon = None,
+            b':' => colon = Some(i),
+            b'A'..=b'Z' => upper = true,
+            b if !b.is_ascii() => ascii = false,
+            b if char::from(b).is_whitespace() => {
+                whitespace = Some(i);
+                break;
+            }
+
+            b if b.is_ascii_control() => control = true,
+
+            _ => (),
+        }
+
+        slashes.push(i, b);
+    }
+
+    if whitespace.is_none() && !ascii {
+        whitespace = from.find(char::is_whitespace);
+    }
+
+    let illegal = match whitespace.is_none() && (control || !ascii) {
+        true => from
+            .char_indices()
+            .find_map(|(i, c)| Some((i, CharClass::of(c)?))),
+        false => None,
+    };
+
+    // The repository and tag end at the last `@`.
+    let (at, slashes, colon) = match at {
+        None => (None, slashes, colon),
+        Some((at, slashes, colon)) => (Some(at), slashes, colon),
+    };
+
+    Bounds {
+        len: from.len(),
+        slashes,
+        at,
+        colon,
+        upper,
+        whitespace,
+        illegal,
+    }
+}
+
+/// Parses a date-like tag into its year, month and day
+fn date(tag: &str) -> Option<(u16, u16, u16)> {
+    let (year, month, day) = match tag.as_bytes() {
+        [y @ .., m0, m1, d0, d1] if y.len() == 4 => (y, [*m0, *m1], [*d0, *d1]),
+        [y @ .., s0, m0, m1, s1, d0, d1] if y.len() == 4 && s0 == s1 && b"-._".contains(s0) => {
+            (y, [*m0, *m1], [*d0, *d1])
+        }
+        _ => return None,
+    };
+
+    let number = |digits: &[u8]| {
+        digits.iter().try_fold(0u16, |n, d| {
+            d.is_ascii_digit().then(|| n * 10 + u16::from(d - b'0'))
+        })
+    };
+
+    let (year, month, day) = (number(year)?, number(&month)?, number(&day)?);
+    match (1..=12).contains(&month) && (1..=31).contains(&day) {
+        true => Some((year, month, day)),
+        false => None,
+    }
+}
+
+fn collapse_slashes(from: &str) -> String {
+    let mut collapsed = String::with_capacity(from.len());
+
+    for c in from.chars() {
+        if c != '/' || !collapsed.ends_with('/') {
+            collapsed.push(c);
+        }
+    }
+
+    collapsed
+}
+
+/// Splits an image reference into its validated components
+#[inline]
+fn split(from: &str) -> Result<Split<'_>, Error> {
+    split_with(from, |digest| Ok(digest.parse()?))
+}
+
+/// Splits an image reference, handling its digest with `digest`
+///
+/// All boundaries are located in a single pass over the input, after which
+/// each component is validated once.
+fn split_with<D>(
+    from: &str,
+    digest: impl FnOnce(&str) -> Result<D, Error>,
+) -> Result<Split<'_, D>, Error> {
+    let bounds = locate(from);
+
+    if bounds.whitespace.is_some() {
+        return Err(Error::Whitespace);
+    }
+
+    if let Some((_, found)) = bounds.illegal {
+        return Err(Error::IllegalCharacter { found });
+    }
+
+    let digest = match bounds.digest() {
+        None => None,
+        Some(range) => {
+            let dig = &from[range];
+            Some((dig, digest(dig)?))
+        }
+    };
+
+    let tag = match bounds.tag() {
+        None => None,
+        Some(range) => {
+            let lbl = &from[range];
+            tag(lbl)?;
+            Some(lbl)
+        }
+    };
+
+    let repository = &from[bounds.repository()];
+
+    Ok(Split {
+        repository,
+        parts: crate::repository::split_at(repository, bounds.slashes)?,
+        tag,
+        digest,
+    })
+}
+
+/// Checks a digest as parsing a [`Digest`] does, without allocating
+fn check_digest(from: &str) -> Result<(), Error> {
+    use crate::digest::Error as DigestError;
+
+    let (algorithm, hex) = from.split_once(':').ok_or(DigestError::Length)?;
+    let algorithm = Algorithm::named(algorithm.as_bytes()).ok_or(DigestError::Algorithm)?;
+
+    if hex.len() != algorithm.hex_len() {
+        return Err(DigestError::Length.into());
+    }
+
+    match hex.bytes().all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f')) {
+        true => Ok(()),
+        false => Err(DigestError::Character.into()),
+    }
+}
+
+/// Lowercases the digest of an image reference, if it has uppercase letters
+fn lowercase_digest(from: &str) -> Option<String> {
+    let bounds = locate(from);
+    let range = bounds.digest().filter(|_| bounds.upper)?;
+    Some([&from[..range.start], &from[range].to_ascii_lowercase()].concat())
+}
+
+#[inline(always)]
+fn tag(from: &str) -> Result<(), Error> {
+    // Registries count bytes, and any byte of a multibyte character falls
+    // through to the last arm below.
+    if from.is_empty() || from.len() > crate::MAX_TAG_LEN {
+        return Err(Error::Tag);
+    }
+
+    for (i, b) in from.bytes().enumerate() {
+        match (i, b) {
+            (_, b'a'..=b'z' | b'A'..=b'Z' | b'0'..=b'9' | b'_') => (),
+            (0, _) => return Err(Error::Tag),
+            (_, b'.' | b'-') => (),
+            _ => return Err(Error::Tag),
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SHA256: &str = "sha256:e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855";
+
+    #[rstest::rstest]
+    #[case(SHA256)]
+    #[case("sha384:38b060a751ac96384cd9327eb1b1e36a21fdb71114be07434c0cc7bf63f6e1da274edebfe76f65fbd51ad2f14898b95b")]
+    #[case("sha512:cf83e1357eefb8bdf1542850d66d8007d620e4050b5715dc83f4a921d36ce9ce47d0d13c5d85f2b0ff8318d2877eec2f63b931bd47417a81a538327af927da3e")]
+    fn test_digest_buf(#[case] input: &str) {
+        let digest: Digest = input.parse().unwrap();
+        let buf = DigestBuf::of(&digest).unwrap();
+
+        let (algorithm, hex) = input.split_once(':').unwrap();
+        assert_eq!(buf.parts(), (algorithm.as_bytes(), hex.as_bytes()));
+    }
+
+    #[test]
+    fn test_digest_buf_overflow() {
+        use core::fmt::Write;
+
+        let mut buf = DigestBuf::of(&SHA256.parse().unwrap()).unwrap();
+        let room = DigestBuf::CAPACITY - SHA256.len();
+
+        assert!(buf.write_str(&"0".repeat(room + 1)).is_err());
+        assert_eq!(buf.len, SHA256.len());
+        assert!(buf.write_str(&"0".repeat(room)).is_ok());
+        assert_eq!(buf.len, DigestBuf::CAPACITY);
+    }
+    #[test]
+    fn test_cmp_digests() {
+        let sha256 = |hex: &str| {
+            format!("sha256:{}", hex.repeat(64))
+                .parse::<Digest>()
+                .unwrap()
+        };
+        let sha512 = |hex: &str| {
+            format!("sha512:{}", hex.repeat(128))
+                .parse::<Digest>()
+                .unwrap()
+        };
+
+        assert_eq!(cmp_digests(&sha256("0"), &sha256("0")), Ordering::Equal);
+        assert_eq!(cmp_digests(&sha256("0"), &sha256("1")), Ordering::Less);
+        assert_eq!(cmp_digests(&sha256("f"), &sha512("0")), Ordering::Less);
+        assert_eq!(cmp_digests(&sha512("0"), &sha256("f")), Ordering::Greater);
     }
 }
@@ -7,6 +7,12 @@
 //! # Features
 //!
 //! - `serde`: Adds serialization/deserialization support via serde
+//! - `sha`: Adds computing SHA-256 digests of content via sha2
+//! - `http`: Adds converting images to `http::Uri` manifest URLs
+//! - `url`: Adds converting registry `url::Url`s to images
+//! - `semver`: Adds comparing tags as semantic versions via semver
+//! - `inline`: Adds repositories stored inline, parsed without allocating
+//! - `intern`: Adds sharing parsed registries between references
 //!
 //! # Safety
 //!
@@ -58,14 +64,35 @@
     unused_results,
     variant_size_differences
 )]
+#![cfg_attr(test, allow(unused_crate_dependencies))]
 
 extern crate alloc;
 
 #[cfg(test)]
 extern crate std;
 
+pub use image::parse;
 pub use oci_digest as digest;
 
 pub mod image;
+pub mod options;
 pub mod registry;
 pub mod repository;
+
+#[cfg(feature = "sha")]
+pub mod sha;
+
+/// the maximum length of a tag
+pub const MAX_TAG_LEN: usize = 128;
+
+/// the maximum length of a registry host, as limited by DNS
+pub const MAX_HOST_LEN: usize = 253;
+
+/// the maximum length of a single registry host label, as limited by DNS
+pub const MAX_LABEL_LEN: usize = 63;
+
+/// the maximum length of a repository name, including its registry
+///
+/// This matches the limit of the reference implementation of the OCI
+/// distribution specification. Tags and digests are not counted.
+pub const MAX_REFERENCE_LEN: usize = 255;
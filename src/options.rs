@@ -0,0 +1,163 @@
+//! Image reference parsing options
+//!
+//! # Examples
+//!
+//! ```rust
+//! use oci_imgref::image::{Error, Image};
+//! use oci_imgref::options::ParseOptions;
+//!
+//! let options = ParseOptions {
+//!     forbid_tag_and_digest: true,
+//!     ..Default::default()
+//! };
+//!
+//! let digest = "sha256:e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855";
+//! let input = format!("quay.io/foo/bar:latest@{digest}");
+//! assert_eq!(Image::parse_with(&input, options), Err(Error::TagAndDigest));
+//! ```
+
+/// options controlling how image references are parsed
+///
+/// The default options parse exactly like [`str::parse`].
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct ParseOptions {
+    /// reject references with both a tag and a digest
+    pub forbid_tag_and_digest: bool,
+
+    /// reject tags ending with `.` or `-` (i.e. `v1.0-`)
+    ///
+    /// The distribution grammar allows these, so this goes beyond the
+    /// specification. Such tags are usually truncated or mistyped versions.
+    pub forbid_trailing_separator: bool,
+
+    /// reject references that do not name a registry
+    pub require_registry: bool,
+
+    /// reject the `library` organization on registries other than Docker Hub
+    ///
+    /// Only Docker Hub keeps its official images under `library`, so
+    /// `quay.io/library/ubuntu` is most likely `docker.io/library/ubuntu`
+    /// pasted with the wrong registry. References without a registry are
+    /// assumed to be on Docker Hub and pass.
+    pub forbid_foreign_library: bool,
+
+    /// tag references with neither a tag nor a digest as `latest`
+    pub default_tag_latest: bool,
+
+    /// lowercase the registry host, which DNS treats case-insensitively
+    ///
+    /// The zone of an IPv6 address (i.e. `eth0` in `[fe80::1%eth0]`) names
+    /// a network interface and keeps its case.
+    pub lowercase_host: bool,
+
+    /// lowercase the digest, which some tools emit in uppercase
+    ///
+    /// The OCI image specification only allows lowercase digests, so they
+    /// are otherwise rejected. This covers the algorithm as well as the
+    /// hex: `SHA256:...` is read as `sha256:...`. It is ignored under
+    /// [`strict`](Self::strict), which always rejects uppercase digests.
+    pub lowercase_digest: bool,
+
+    /// assume Docker Hub when no registry is named
+    ///
+    /// Like the Docker CLI, this also normalizes the aliases of Docker Hub
+    /// to `docker.io` and places images without an organization in the
+    /// `library` organization of official images.
+    pub default_docker_hub: bool,
+
+    /// read a single-label first component as a registry (i.e. `myhost` in
+    /// `myhost/foo`)
+    ///
+    /// References are ambiguous here: a first component without a `.` or a
+    /// port, other than `localhost`, is normally an organization, as on
+    /// Docker Hub. This suits intranets whose registries have bare host
+    /// names, but then `foo/bar` can no longer name a Docker Hub image.
+    /// Components are only reread when exactly one precedes the container,
+    /// since with more the first is always a registry. Such an image does not
+    /// parse back from its string form, so [`Image::is_consistent`](crate::image::Image::is_consistent) is
+    /// `false` for it.
+    pub single_label_registry: bool,
+
+    /// collapse runs of `/` before parsing (i.e. `docker.io//library//ubuntu`)
+    ///
+    /// This forgives copy-paste artifacts. It is ignored under
+    /// [`strict`](Self::strict), which always rejects empty path components.
+    pub collapse_slashes: bool,
+
+    /// check the result against the strict OCI grammar
+    ///
+    /// See [`Image::validate_strict`](crate::image::Image::validate_strict).
+    pub strict: bool,
+}
+
+impl ParseOptions {
+    /// Returns options that parse like `docker pull`
+    ///
+    /// This enables:
+    ///
+    /// - [`lowercase_host`](Self::lowercase_host), so `Quay.IO/foo` is `quay.io/foo`
+    /// - [`default_docker_hub`](Self::default_docker_hub), so `ubuntu` is
+    ///   `docker.io/library/ubuntu`
+    /// - [`default_tag_latest`](Self::default_tag_latest), so `ubuntu` is
+    ///   `ubuntu:latest`
+    ///
+    /// ```rust
+    /// use oci_imgref::image::Image;
+    /// use oci_imgref::options::ParseOptions;
+    ///
+    /// let image = Image::parse_with("ubuntu", ParseOptions::docker_compat())?;
+    /// assert_eq!(image.to_string(), "docker.io/library/ubuntu:latest");
+    /// # Ok::<(), oci_imgref::image::Error>(())
+    /// ```
+    pub const fn docker_compat() -> Self {
+        Self {
+            forbid_tag_and_digest: false,
+            forbid_trailing_separator: false,
+            require_registry: false,
+            forbid_foreign_library: false,
+            default_tag_latest: true,
+            lowercase_host: true,
+            lowercase_digest: false,
+            default_docker_hub: true,
+            single_label_registry: false,
+            collapse_slashes: false,
+            strict: false,
+        }
+    }
+
+    /// Returns options that accept only what the OCI specification allows
+    ///
+    /// This enables [`strict`](Self::strict) and no implicit defaults, so a
+    /// reference passes only if:
+    ///
+    /// - every path component matches `[a-z0-9]+((\.|_|__|-+)[a-z0-9]+)*`
+    /// - the tag matches `[A-Za-z0-9_][A-Za-z0-9._-]*` and has at most 128
+    ///   characters
+    /// - the repository name, with registry, has at most 255 characters
+    /// - the registry host is a valid domain name or IPv6 address
+    /// - the digest is valid and lowercase
+    ///
+    /// ```rust
+    /// use oci_imgref::image::Image;
+    /// use oci_imgref::options::ParseOptions;
+    ///
+    /// assert!(Image::parse_with("foo/bar", ParseOptions::strict_oci()).is_ok());
+    /// assert!(Image::parse_with("Foo/Bar", ParseOptions::strict_oci()).is_err());
+    /// assert!(Image::parse_with("foo:v1.", ParseOptions::strict_oci()).is_ok());
+    /// ```
+    pub const fn strict_oci() -> Self {
+        Self {
+            forbid_tag_and_digest: false,
+            forbid_trailing_separator: false,
+            require_registry: false,
+            forbid_foreign_library: false,
+            default_tag_latest: false,
+            lowercase_host: false,
+            lowercase_digest: false,
+            default_docker_hub: false,
+            single_label_registry: false,
+            collapse_slashes: false,
+            strict: true,
+        }
+    }
+}
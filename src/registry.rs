@@ -20,16 +20,84 @@
 
 use core::num::NonZeroU16;
 
+use alloc::borrow::Cow;
+use alloc::format;
 use alloc::string::{String, ToString};
+#[cfg(feature = "intern")]
+use alloc::{collections::BTreeMap, sync::Arc};
 
 /// a registry parsing error
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, thiserror::Error, displaydoc::Display)]
 pub enum Error {
-    /// invalid host
+    /// invalid host: empty
     Host,
 
-    /// invalid Port
+    /// invalid host: empty label
+    EmptyLabel,
+
+    /// invalid host: label longer than 63 characters
+    LabelTooLong,
+
+    /// invalid host: longer than 253 characters
+    HostTooLong,
+
+    /// invalid host: invalid character (expected `.`-separated labels of `[A-Za-z0-9-]`)
+    InvalidChar,
+
+    /// invalid host: label starts with a dash
+    LeadingDash,
+
+    /// invalid host: label ends with a dash
+    TrailingDash,
+
+    /// invalid host: invalid IPv6 address (expected `[addr]` or `[addr%zone]`)
+    Ipv6,
+
+    /// invalid host: invalid IPv6 zone (expected `[A-Za-z0-9._~-]+`)
+    Zone,
+
+    /// invalid port: zero (expected 1-65535)
     Port,
+
+    /// invalid port: not a number (expected 1-65535)
+    PortNotNumeric,
+
+    /// invalid port: out of range (expected 1-65535)
+    PortOverflow,
+
+    /// invalid UTF-8
+    Utf8,
+}
+
+/// a transport scheme for reaching a registry
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Scheme {
+    /// plain HTTP (default port 80)
+    Http,
+
+    /// HTTP over TLS (default port 443)
+    #[default]
+    Https,
+}
+
+impl Scheme {
+    /// Returns the default port of the scheme
+    #[inline]
+    pub const fn default_port(self) -> u16 {
+        match self {
+            Self::Http => 80,
+            Self::Https => 443,
+        }
+    }
+}
+
+impl core::fmt::Display for Scheme {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Http => write!(f, "http"),
+            Self::Https => write!(f, "https"),
+        }
+    }
 }
 
 /// a container registry
@@ -50,17 +118,292 @@ pub enum Error {
 ///
 /// assert_eq!(registry.to_string(), "quay.io:5000");
 /// ```
-#[derive(Clone, Debug, PartialEq, Eq, Hash)]
-#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
-#[cfg_attr(feature = "serde", serde(try_from = "String", into = "String"))]
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct Registry {
     /// the host (i.e. `quay.io` in `quay.io:1234`)
-    pub host: String,
+    ///
+    /// IPv6 addresses are stored with their brackets and optional zone
+    /// (i.e. `[fe80::1%eth0]` in `[fe80::1%eth0]:1234`).
+    ///
+    /// Constant registries, such as [`Registry::docker_hub`], borrow their
+    /// host, while parsed ones own it.
+    pub host: Cow<'static, str>,
 
     /// the port (i.e. `1234` in `quay.io:1234`)
     pub port: Option<NonZeroU16>,
 }
 
+impl Registry {
+    /// Returns Docker Hub, the registry assumed when a reference names none
+    #[inline]
+    pub const fn docker_hub() -> Self {
+        Self::from_static("docker.io", None)
+    }
+
+    /// Returns a registry borrowing a static host, without allocating
+    ///
+    /// Being a `const fn`, this suits registries fixed at compile time. The
+    /// host is not validated, so it must be one that [`str::parse`] accepts.
+    ///
+    /// ```rust
+    /// use core::num::NonZeroU16;
+    /// use oci_imgref::registry::Registry;
+    ///
+    /// const MIRROR: Registry = Registry::from_static("mirror.example.com", NonZeroU16::new(5000));
+    /// assert_eq!(MIRROR, "mirror.example.com:5000".parse().unwrap());
+    /// ```
+    #[inline]
+    pub const fn from_static(host: &'static str, port: Option<NonZeroU16>) -> Self {
+        Self {
+            host: Cow::Borrowed(host),
+            port,
+        }
+    }
+
+    /// Starts building a registry
+    ///
+    /// ```rust
+    /// use oci_imgref::registry::Registry;
+    ///
+    /// let registry = Registry::builder().host("quay.io").port(5000).build()?;
+    /// assert_eq!(registry.to_string(), "quay.io:5000");
+    /// # Ok::<(), oci_imgref::registry::Error>(())
+    /// ```
+    pub const fn builder() -> RegistryBuilder {
+        RegistryBuilder {
+            host: String::new(),
+            port: None,
+        }
+    }
+
+    /// Returns `true` if this is Docker Hub under any of its host names
+    ///
+    /// Besides `docker.io`, Docker Hub is reachable as `index.docker.io`,
+    /// `registry-1.docker.io` and `registry.hub.docker.com`. Since Docker
+    /// Hub is only served over HTTPS, an explicit port 443 (the default of
+    /// [`Scheme::Https`]) names it too, as in `docker.io:443`.
+    ///
+    /// ```rust
+    /// use oci_imgref::registry::Registry;
+    ///
+    /// assert!("docker.io:443".parse::<Registry>()?.is_default());
+    /// assert!(!"docker.io:5000".parse::<Registry>()?.is_default());
+    /// # Ok::<(), oci_imgref::registry::Error>(())
+    /// ```
+    pub fn is_default(&self) -> bool {
+        const ALIASES: &[&str] = &[
+            "docker.io",
+            "index.docker.io",
+            "registry-1.docker.io",
+            "registry.hub.docker.com",
+        ];
+
+        let https = Scheme::Https.default_port();
+        self.port_or(https) == https && ALIASES.iter().any(|a| a.eq_ignore_ascii_case(&self.host))
+    }
+
+    /// Returns `true` if the host is an IPv4 or IPv6 address literal
+    ///
+    /// Clients may skip DNS for such hosts, and must not send them as a TLS
+    /// server name. IPv6 addresses are recognized with their brackets and
+    /// optional zone, as stored in [`Registry::host`].
+    ///
+    /// ```rust
+    /// use oci_imgref::registry::Registry;
+    ///
+    /// assert!("127.0.0.1:5000".parse::<Registry>()?.host_is_ip());
+    /// assert!("[fe80::1%eth0]".parse::<Registry>()?.host_is_ip());
+    /// assert!(!"quay.io".parse::<Registry>()?.host_is_ip());
+    /// # Ok::<(), oci_imgref::registry::Error>(())
+    /// ```
+    pub fn host_is_ip(&self) -> bool {
+        match self
+            .host
+            .strip_prefix('[')
+            .and_then(|h| h.strip_suffix(']'))
+        {
+            Some(addr) => {
+                let addr = addr.split_once('%').map_or(addr, |(addr, _)| addr);
+                addr.parse::<core::net::Ipv6Addr>().is_ok()
+            }
+
+            None => self.host.parse::<core::net::Ipv4Addr>().is_ok(),
+        }
+    }
+
+    /// Returns the base URL of the registry's v2 API
+    ///
+    /// The port is always included, falling back to the default port of the
+    /// scheme when the registry has none.
+    ///
+    /// ```rust
+    /// use oci_imgref::registry::{Registry, Scheme};
+    ///
+    /// let registry: Registry = "quay.io".parse().unwrap();
+    /// assert_eq!(registry.api_base_url(Scheme::Https), "https://quay.io:443/v2/");
+    /// ```
+    pub fn api_base_url(&self, scheme: Scheme) -> String {
+        format!(
+            "{}://{}:{}/v2/",
+            scheme,
+            self.host,
+            self.port_or(scheme.default_port())
+        )
+    }
+
+    /// Returns the `host[:port]` authority, as in a URL
+    ///
+    /// This is the [`Display`](core::fmt::Display) form, so the port is left
+    /// out when the registry has none.
+    ///
+    /// ```rust
+    /// use oci_imgref::registry::Registry;
+    ///
+    /// let registry: Registry = "quay.io".parse().unwrap();
+    /// assert_eq!(registry.authority(), "quay.io");
+    /// ```
+    #[inline]
+    pub fn authority(&self) -> String {
+        self.to_string()
+    }
+
+    /// Returns the `host:port` authority, falling back to `default` if the
+    /// registry has no port
+    ///
+    /// ```rust
+    /// use oci_imgref::registry::{Registry, Scheme};
+    ///
+    /// let registry: Registry = "quay.io".parse().unwrap();
+    /// let https = Scheme::Https.default_port();
+    /// assert_eq!(registry.authority_with_default_port(https), "quay.io:443");
+    /// ```
+    pub fn authority_with_default_port(&self, default: u16) -> String {
+        format!("{}:{}", self.host, self.port_or(default))
+    }
+
+    /// Returns `true` if the registry matches `pattern`
+    ///
+    /// This is [`RegistryPattern::matches`] with the arguments swapped.
+    ///
+    /// ```rust
+    /// use oci_imgref::registry::Registry;
+    ///
+    /// let registry: Registry = "registry.example.com:5000".parse()?;
+    /// assert!(registry.matches(&"*.example.com".parse()?));
+    /// assert!(!registry.matches(&"*.example.com:443".parse()?));
+    /// # Ok::<(), oci_imgref::registry::Error>(())
+    /// ```
+    #[inline]
+    pub fn matches(&self, pattern: &RegistryPattern) -> bool {
+        pattern.matches(self)
+    }
+
+    /// Returns the port, or `default` if the registry has none
+    ///
+    /// ```rust
+    /// use oci_imgref::registry::Registry;
+    ///
+    /// let registry: Registry = "quay.io:5000".parse().unwrap();
+    /// assert_eq!(registry.port_or(443), 5000);
+    ///
+    /// let registry: Registry = "quay.io".parse().unwrap();
+    /// assert_eq!(registry.port_or(443), 443);
+    /// ```
+    #[inline]
+    pub const fn port_or(&self, default: u16) -> u16 {
+        match self.port {
+            Some(port) => port.get(),
+            None => default,
+        }
+    }
+
+    /// Returns the length of the registry as written by [`Display`](core::fmt::Display)
+    ///
+    /// ```rust
+    /// use oci_imgref::registry::Registry;
+    ///
+    /// let registry: Registry = "quay.io:5000".parse().unwrap();
+    /// assert_eq!(registry.len(), 12);
+    /// ```
+    #[allow(clippy::len_without_is_empty)] // registries are never empty
+    pub fn len(&self) -> usize {
+        let port = self.port.map_or(0, |p| p.ilog10() as usize + 2);
+        self.host.len() + port
+    }
+
+    /// Replaces the host after validating it
+    ///
+    /// On error the registry is left unchanged.
+    ///
+    /// ```rust
+    /// use oci_imgref::registry::{Error, Registry};
+    ///
+    /// let mut registry: Registry = "quay.io:5000".parse()?;
+    /// registry.set_host("ghcr.io")?;
+    /// assert_eq!(registry.to_string(), "ghcr.io:5000");
+    ///
+    /// assert_eq!(registry.set_host("-ghcr.io"), Err(Error::LeadingDash));
+    /// assert_eq!(registry.host, "ghcr.io");
+    /// # Ok::<(), oci_imgref::registry::Error>(())
+    /// ```
+    pub fn set_host(&mut self, host: &str) -> Result<(), Error> {
+        validate(host)?;
+        self.host = Cow::Owned(host.into());
+        Ok(())
+    }
+
+    /// Replaces the port, rejecting port zero
+    ///
+    /// On error the registry is left unchanged.
+    pub fn set_port(&mut self, port: Option<u16>) -> Result<(), Error> {
+        self.port = match port {
+            None => None,
+            Some(port) => Some(NonZeroU16::new(port).ok_or(Error::Port)?),
+        };
+
+        Ok(())
+    }
+}
+
+/// a builder for [`Registry`], created by [`Registry::builder`]
+#[derive(Clone, Debug, Default)]
+#[must_use]
+pub struct RegistryBuilder {
+    host: String,
+    port: Option<u16>,
+}
+
+impl RegistryBuilder {
+    /// Sets the host
+    pub fn host(mut self, host: &str) -> Self {
+        self.host = host.into();
+        self
+    }
+
+    /// Sets the port
+    pub fn port(mut self, port: u16) -> Self {
+        self.port = Some(port);
+        self
+    }
+
+    /// Validates the host and port and returns the registry
+    ///
+    /// A missing host is reported as [`Error::Host`].
+    pub fn build(self) -> Result<Registry, Error> {
+        validate(&self.host)?;
+
+        let port = match self.port {
+            None => None,
+            Some(port) => Some(NonZeroU16::new(port).ok_or(Error::Port)?),
+        };
+
+        Ok(Registry {
+            host: self.host.into(),
+            port,
+        })
+    }
+}
+
 impl core::fmt::Display for Registry {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         write!(f, "{}", self.host)?;
@@ -84,33 +427,10 @@ impl core::str::FromStr for Registry {
     type Err = Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let (host, port) = s
-            .split_once(':')
-            .map(|(h, p)| {
-                let port: u16 = p.parse().or(Err(Error::Port))?;
-                let port = NonZeroU16::new(port).ok_or(Error::Port)?;
-                Ok((h, Some(port)))
-            })
-            .unwrap_or(Ok((s, None)))?;
-
-        if host.is_empty() {
-            return Err(Error::Host);
-        }
-
-        for segment in host.split('.') {
-            for c in segment.chars() {
-                if !c.is_ascii_alphanumeric() && c != '-' {
-                    return Err(Error::Host);
-                }
-            }
-
-            if segment.is_empty() || segment.starts_with('-') || segment.ends_with('-') {
-                return Err(Error::Host);
-            }
-        }
+        let (host, port) = split(s)?;
 
         Ok(Self {
-            host: host.into(),
+            host: Cow::Owned(host.into()),
             port,
         })
     }
@@ -125,27 +445,647 @@ impl TryFrom<String> for Registry {
     }
 }
 
+impl TryFrom<&str> for Registry {
+    type Error = Error;
+
+    #[inline(always)]
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl TryFrom<&[u8]> for Registry {
+    type Error = Error;
+
+    #[inline(always)]
+    fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
+        core::str::from_utf8(value).or(Err(Error::Utf8))?.parse()
+    }
+}
+
+/// Builds a registry from a separate host and port
+///
+/// An empty port means the registry has no port.
+///
+/// ```rust
+/// use oci_imgref::registry::Registry;
+///
+/// let registry = Registry::try_from(("quay.io", "5000"))?;
+/// assert_eq!(registry.to_string(), "quay.io:5000");
+///
+/// let registry = Registry::try_from(("quay.io", ""))?;
+/// assert_eq!(registry.port, None);
+/// # Ok::<(), oci_imgref::registry::Error>(())
+/// ```
+impl TryFrom<(&str, &str)> for Registry {
+    type Error = Error;
+
+    fn try_from((host, p): (&str, &str)) -> Result<Self, Self::Error> {
+        let port = match p {
+            "" => None,
+            p => Some(port(p)?),
+        };
+
+        validate(host)?;
+
+        Ok(Self {
+            host: Cow::Owned(host.into()),
+            port,
+        })
+    }
+}
+
+/// a pattern matching registries, as used in mirror and proxy rules
+///
+/// Patterns are parsed from strings of a [`HostPattern`] optionally
+/// followed by `:port`:
+///
+/// - `*` matches any host
+/// - `*.example.com` matches hosts with one or more labels before
+///   `example.com`, but not `example.com` itself
+/// - `mirror-*.example.com` matches hosts with exactly one label before
+///   `example.com` that starts with `mirror-` and continues with at least
+///   one more character
+/// - anything else must be a valid host and matches only that host
+///
+/// Hosts are compared case-insensitively. A pattern without a port matches
+/// any port, while one with a port matches only registries naming exactly
+/// that port.
+///
+/// ```rust
+/// use oci_imgref::registry::{Registry, RegistryPattern};
+///
+/// let pattern: RegistryPattern = "*.internal.example.com".parse()?;
+/// let registry: Registry = "a.b.internal.example.com:5000".parse()?;
+/// assert!(pattern.matches(&registry));
+///
+/// let pattern: RegistryPattern = "*.internal.example.com:443".parse()?;
+/// assert!(!pattern.matches(&registry));
+/// # Ok::<(), oci_imgref::registry::Error>(())
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct RegistryPattern {
+    /// the hosts matched
+    pub host: HostPattern,
+
+    /// the port matched, or `None` to match any port
+    pub port: Option<NonZeroU16>,
+}
+
+/// the host part of a [`RegistryPattern`]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum HostPattern {
+    /// any host (i.e. `*`)
+    Any,
+
+    /// the subdomains of a host (i.e. `*.example.com`, stored as `example.com`)
+    Subdomains(String),
+
+    /// exactly one host (i.e. `example.com`)
+    Exact(String),
+
+    /// a host whose first label starts with a prefix (i.e. `mirror-*.example.com`)
+    LabelPrefix {
+        /// the start of the first label (i.e. `mirror-`)
+        prefix: String,
+
+        /// the rest of the host (i.e. `example.com`)
+        domain: String,
+    },
+}
+
+impl RegistryPattern {
+    /// Returns `true` if the host and port of `registry` match the pattern
+    #[inline]
+    pub fn matches(&self, registry: &Registry) -> bool {
+        self.host.matches(&registry.host) && (self.port.is_none() || self.port == registry.port)
+    }
+}
+
+impl HostPattern {
+    /// Returns `true` if `host` matches the pattern
+    pub fn matches(&self, host: &str) -> bool {
+        match self {
+            Self::Any => true,
+            Self::Exact(pattern) => pattern.eq_ignore_ascii_case(host),
+            Self::Subdomains(suffix) => subdomain(host, suffix).is_some(),
+            Self::LabelPrefix { prefix, domain } => match subdomain(host, domain) {
+                None => false,
+                Some(label) => {
+                    label.len() > prefix.len()
+                        && !label.contains(&b'.')
+                        && label[..prefix.len()].eq_ignore_ascii_case(prefix.as_bytes())
+                }
+            },
+        }
+    }
+}
+
+/// Returns the non-empty part of `host` before `.domain`, if it has one
+fn subdomain<'a>(host: &'a str, domain: &str) -> Option<&'a [u8]> {
+    let host = host.as_bytes();
+    let dot = host.len().checked_sub(domain.len() + 1)?;
+
+    match dot > 0 && host[dot] == b'.' && host[dot + 1..].eq_ignore_ascii_case(domain.as_bytes()) {
+        true => Some(&host[..dot]),
+        false => None,
+    }
+}
+
+impl core::str::FromStr for RegistryPattern {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let Some((prefix, rest)) = s.split_once('*') else {
+            let (host, port) = split(s)?;
+            let host = HostPattern::Exact(host.into());
+            return Ok(Self { host, port });
+        };
+
+        let Some(domain) = rest.strip_prefix('.') else {
+            if !prefix.is_empty() {
+                return Err(Error::InvalidChar);
+            }
+
+            let port = match rest {
+                "" => None,
+                rest => Some(port(rest.strip_prefix(':').ok_or(Error::InvalidChar)?)?),
+            };
+
+            return Ok(Self {
+                host: HostPattern::Any,
+                port,
+            });
+        };
+
+        if domain.starts_with('[') {
+            return Err(Error::InvalidChar);
+        }
+
+        let (domain, port) = split(domain)?;
+        let host = match prefix {
+            "" => HostPattern::Subdomains(domain.into()),
+            prefix if prefix.starts_with('-') => return Err(Error::LeadingDash),
+            prefix
+                if !prefix
+                    .bytes()
+                    .all(|b| b.is_ascii_alphanumeric() || b == b'-') =>
+            {
+                return Err(Error::InvalidChar)
+            }
+            prefix => HostPattern::LabelPrefix {
+                prefix: prefix.into(),
+                domain: domain.into(),
+            },
+        };
+
+        Ok(Self { host, port })
+    }
+}
+
+impl core::fmt::Display for RegistryPattern {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match &self.host {
+            HostPattern::Any => write!(f, "*")?,
+            HostPattern::Subdomains(suffix) => write!(f, "*.{}", suffix)?,
+            HostPattern::Exact(host) => write!(f, "{}", host)?,
+            HostPattern::LabelPrefix { prefix, domain } => write!(f, "{}*.{}", prefix, domain)?,
+        }
+
+        match self.port {
+            Some(port) => write!(f, ":{}", port),
+            None => Ok(()),
+        }
+    }
+}
+
+/// a cache of shared registries
+///
+/// Parsing many references that share a handful of registries allocates a
+/// fresh host for every one of them. An interner parses each distinct
+/// registry once and hands out shared copies afterwards.
+///
+/// ```rust
+/// use oci_imgref::registry::RegistryInterner;
+/// use std::sync::Arc;
+///
+/// let mut interner = RegistryInterner::new();
+/// let a = interner.parse("quay.io")?;
+/// let b = interner.parse("quay.io")?;
+/// assert!(Arc::ptr_eq(&a, &b));
+/// # Ok::<(), oci_imgref::registry::Error>(())
+/// ```
+#[cfg(feature = "intern")]
+#[derive(Clone, Debug, Default)]
+pub struct RegistryInterner {
+    cache: BTreeMap<String, Arc<Registry>>,
+}
+
+#[cfg(feature = "intern")]
+impl RegistryInterner {
+    /// Creates an empty interner
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Parses a registry, sharing the result with earlier identical inputs
+    pub fn parse(&mut self, s: &str) -> Result<Arc<Registry>, Error> {
+        if let Some(registry) = self.cache.get(s) {
+            return Ok(registry.clone());
+        }
+
+        let registry = Arc::new(s.parse()?);
+        Ok(self.cache.entry(s.into()).or_insert(registry).clone())
+    }
+
+    /// Returns the number of distinct registries held
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.cache.len()
+    }
+
+    /// Returns `true` if no registries are held
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.cache.is_empty()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for Registry {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            serializer.collect_str(self)
+        } else {
+            (&self.host, self.port).serialize(serializer)
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Registry {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        use serde::de::Error as _;
+
+        if deserializer.is_human_readable() {
+            let value = String::deserialize(deserializer)?;
+            value.parse().map_err(D::Error::custom)
+        } else {
+            let (host, port) = <(String, Option<NonZeroU16>)>::deserialize(deserializer)?;
+            validate(&host).map_err(D::Error::custom)?;
+            Ok(Self {
+                host: host.into(),
+                port,
+            })
+        }
+    }
+}
+
+/// Splits a registry into its validated host and port without allocating
+#[inline(always)]
+pub(crate) fn split(s: &str) -> Result<(&str, Option<NonZeroU16>), Error> {
+    let (host, port) = match s.strip_prefix('[') {
+        // `[fe80::1%eth0]:5000`
+        Some(rest) => {
+            let end = rest.find(']').ok_or(Error::Ipv6)? + 2;
+            match &s[end..] {
+                "" => (&s[..end], None),
+                p => match p.strip_prefix(':') {
+                    Some(p) => (&s[..end], Some(port(p)?)),
+                    None => return Err(Error::Ipv6),
+                },
+            }
+        }
+
+        // `:5000` names a port but no host
+        None if s.starts_with(':') => return Err(Error::Host),
+
+        None => s
+            .split_once(':')
+            .map(|(h, p)| Ok((h, Some(port(p)?))))
+            .unwrap_or(Ok((s, None)))?,
+    };
+
+    validate(host)?;
+    Ok((host, port))
+}
+
+#[inline(always)]
+fn port(from: &str) -> Result<NonZeroU16, Error> {
+    if from.is_empty() || !from.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(Error::PortNotNumeric);
+    }
+
+    let port: u16 = from.parse().or(Err(Error::PortOverflow))?;
+    NonZeroU16::new(port).ok_or(Error::Port)
+}
+
+/// Lowercases a host, keeping the case of any IPv6 zone
+///
+/// DNS names are case-insensitive, but a zone names a network interface,
+/// which is not.
+pub(crate) fn lowercase(host: &mut Cow<'static, str>) {
+    let end = host.find('%').unwrap_or(host.len());
+    host.to_mut()[..end].make_ascii_lowercase();
+}
+
+#[inline(always)]
+pub(crate) fn validate(host: &str) -> Result<(), Error> {
+    if host.is_empty() {
+        return Err(Error::Host);
+    }
+
+    if host.len() > crate::MAX_HOST_LEN {
+        return Err(Error::HostTooLong);
+    }
+
+    if let Some(addr) = host.strip_prefix('[') {
+        let addr = addr.strip_suffix(']').ok_or(Error::Ipv6)?;
+
+        let addr = match addr.split_once('%') {
+            None => addr,
+            Some((addr, zone)) => {
+                let valid = |c: char| c.is_ascii_alphanumeric() || "-._~".contains(c);
+                if zone.is_empty() || !zone.chars().all(valid) {
+                    return Err(Error::Zone);
+                }
+
+                addr
+            }
+        };
+
+        return match addr.parse::<core::net::Ipv6Addr>() {
+            Ok(..) => Ok(()),
+            Err(..) => Err(Error::Ipv6),
+        };
+    }
+
+    for label in host.split('.') {
+        if label.is_empty() {
+            return Err(Error::EmptyLabel);
+        }
+
+        if label.len() > crate::MAX_LABEL_LEN {
+            return Err(Error::LabelTooLong);
+        }
+
+        if !label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+            return Err(Error::InvalidChar);
+        }
+
+        if label.starts_with('-') {
+            return Err(Error::LeadingDash);
+        }
+
+        if label.ends_with('-') {
+            return Err(Error::TrailingDash);
+        }
+    }
+
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[rstest::rstest]
+    #[case("quay.io", Scheme::Https, "https://quay.io:443/v2/")]
+    #[case("quay.io", Scheme::Http, "http://quay.io:80/v2/")]
+    #[case("localhost:5000", Scheme::Https, "https://localhost:5000/v2/")]
+    #[case("localhost:5000", Scheme::Http, "http://localhost:5000/v2/")]
+    #[case("[::1]:5000", Scheme::Http, "http://[::1]:5000/v2/")]
+    fn api_base_url(#[case] input: &str, #[case] scheme: Scheme, #[case] url: &str) {
+        let registry: Registry = input.parse().unwrap();
+        assert_eq!(registry.api_base_url(scheme), url);
+    }
+
+    #[rstest::rstest]
+    #[case("quay.io", "quay.io", "quay.io:443")]
+    #[case("quay.io:5000", "quay.io:5000", "quay.io:5000")]
+    #[case("[::1]", "[::1]", "[::1]:443")]
+    fn authorities(#[case] input: &str, #[case] authority: &str, #[case] ported: &str) {
+        let registry: Registry = input.parse().unwrap();
+        assert_eq!(registry.authority(), authority);
+        assert_eq!(registry.authority_with_default_port(443), ported);
+        assert_eq!(registry.to_string(), input);
+    }
+
+    #[rstest::rstest]
+    #[case(("quay.io", "443"), Ok(("quay.io", NonZeroU16::new(443))))]
+    #[case(("quay.io", ""), Ok(("quay.io", None)))]
+    #[case(("[::1]", "5000"), Ok(("[::1]", NonZeroU16::new(5000))))]
+    #[case(("quay.io", "0"), Err(Error::Port))]
+    #[case(("quay.io", "https"), Err(Error::PortNotNumeric))]
+    #[case(("quay.io", "65536"), Err(Error::PortOverflow))]
+    #[case(("quay.io:443", ""), Err(Error::InvalidChar))]
+    #[case(("", "443"), Err(Error::Host))]
+    fn host_and_port(
+        #[case] input: (&str, &str),
+        #[case] expected: Result<(&str, Option<NonZeroU16>), Error>,
+    ) {
+        let registry = Registry::try_from(input).map(|r| (r.host, r.port));
+        assert_eq!(registry, expected.map(|(h, p)| (h.into(), p)));
+    }
+
+    #[rstest::rstest]
+    #[case("127.0.0.1", true)]
+    #[case("192.0.2.1:5000", true)]
+    #[case("[::1]:5000", true)]
+    #[case("[fe80::1%eth0]", true)]
+    #[case("[::ffff:192.0.2.1]", true)]
+    #[case("quay.io", false)]
+    #[case("localhost:5000", false)]
+    #[case("1.2.3", false)]
+    #[case("1.2.3.256", false)]
+    fn host_is_ip(#[case] input: &str, #[case] ip: bool) {
+        let registry: Registry = input.parse().unwrap();
+        assert_eq!(registry.host_is_ip(), ip);
+    }
+
+    #[rstest::rstest]
+    #[case("[::1]")]
+    #[case("[::1]:5000")]
+    #[case("[fe80::1%eth0]")]
+    #[case("[fe80::1%eth0]:443")]
+    #[case("[2001:db8::8a2e:370:7334]:65535")]
+    #[case("[::ffff:192.0.2.1]:1")]
+    fn ipv6_round_trip(#[case] input: &str) {
+        let registry: Registry = input.parse().unwrap();
+        assert!(registry.host.starts_with('[') && registry.host.ends_with(']'));
+        assert_eq!(registry.to_string(), input);
+        assert_eq!(registry.to_string().parse(), Ok(registry));
+    }
+
+    #[rstest::rstest]
+    #[case("quay.io")]
+    #[case("quay.io:1")]
+    #[case("quay.io:9")]
+    #[case("quay.io:10")]
+    #[case("quay.io:999")]
+    #[case("quay.io:1000")]
+    #[case("quay.io:65535")]
+    #[case("[fe80::1%eth0]:443")]
+    fn len(#[case] input: &str) {
+        let registry: Registry = input.parse().unwrap();
+        assert_eq!(registry.len(), input.len());
+    }
+
+    #[rstest::rstest]
+    #[case(Registry::builder().host("quay.io"), Ok("quay.io"))]
+    #[case(Registry::builder().host("[::1]").port(5000), Ok("[::1]:5000"))]
+    #[case(Registry::builder().port(5000), Err(Error::Host))]
+    #[case(Registry::builder().host("quay..io"), Err(Error::EmptyLabel))]
+    #[case(Registry::builder().host("quay.io:5000"), Err(Error::InvalidChar))]
+    #[case(Registry::builder().host("quay.io").port(0), Err(Error::Port))]
+    fn builder(#[case] input: RegistryBuilder, #[case] expected: Result<&str, Error>) {
+        let expected = expected.map(|s| s.parse().unwrap());
+        assert_eq!(input.build(), expected);
+    }
+
+    #[rstest::rstest]
+    #[case("*", "quay.io", true)]
+    #[case("*", "[::1]:5000", true)]
+    #[case("quay.io", "quay.io", true)]
+    #[case("quay.io", "QUAY.io:5000", true)]
+    #[case("quay.io", "a.quay.io", false)]
+    #[case("*.example.com", "a.example.com", true)]
+    #[case("*.example.com", "a.b.example.com:443", true)]
+    #[case("*.Example.COM", "A.example.com", true)]
+    #[case("*.example.com", "example.com", false)]
+    #[case("*.example.com", "aexample.com", false)]
+    #[case("*.example.com", "example.com.evil.io", false)]
+    #[case("[::1]", "[::1]:5000", true)]
+    #[case("[::1]:5000", "[::1]:5000", true)]
+    #[case("quay.io:5000", "quay.io:5000", true)]
+    #[case("quay.io:5000", "quay.io:5001", false)]
+    #[case("quay.io:443", "quay.io", false)]
+    #[case("*.example.com:5000", "a.example.com:5000", true)]
+    #[case("*.example.com:5000", "a.example.com", false)]
+    #[case("*:5000", "quay.io:5000", true)]
+    #[case("*:5000", "quay.io", false)]
+    #[case("mirror-*.example.com", "mirror-1.example.com", true)]
+    #[case("mirror-*.example.com", "mirror-2.example.com:5000", true)]
+    #[case("mirror-*.example.com", "MIRROR-10.Example.com", true)]
+    #[case("mirror-*.example.com", "mirror.example.com", false)]
+    #[case("mirror-*.example.com", "mirror-1.eu.example.com", false)]
+    #[case("mirror-*.example.com", "a.mirror-1.example.com", false)]
+    #[case("mirror-*.example.com", "cache-1.example.com", false)]
+    #[case("mirror-*.example.com:5000", "mirror-1.example.com", false)]
+    fn pattern_matches(#[case] pattern: &str, #[case] host: &str, #[case] matches: bool) {
+        let registry: Registry = host.parse().unwrap();
+        let parsed: RegistryPattern = pattern.parse().unwrap();
+        assert_eq!(parsed.matches(&registry), matches);
+        assert_eq!(registry.matches(&parsed), matches);
+        assert_eq!(parsed.to_string(), pattern);
+    }
+
+    #[rstest::rstest]
+    #[case("", Error::Host)]
+    #[case("*.", Error::Host)]
+    #[case("**", Error::InvalidChar)]
+    #[case("a.*.com", Error::InvalidChar)]
+    #[case("*.[::1]", Error::InvalidChar)]
+    #[case("mirror-*", Error::InvalidChar)]
+    #[case("-mirror*.example.com", Error::LeadingDash)]
+    #[case("a.mirror-*.example.com", Error::InvalidChar)]
+    #[case("mirror-*.*.example.com", Error::InvalidChar)]
+    #[case("*5000", Error::InvalidChar)]
+    #[case("*:0", Error::Port)]
+    #[case("*.quay.io:", Error::PortNotNumeric)]
+    fn pattern_errors(#[case] pattern: &str, #[case] error: Error) {
+        assert_eq!(pattern.parse::<RegistryPattern>(), Err(error));
+    }
+
+    #[test]
+    fn setters() {
+        let mut registry: Registry = "quay.io:5000".parse().unwrap();
+        let before = registry.clone();
+
+        assert_eq!(registry.set_host("quay.io:443"), Err(Error::InvalidChar));
+        assert_eq!(registry.set_host(""), Err(Error::Host));
+        assert_eq!(registry.set_port(Some(0)), Err(Error::Port));
+        assert_eq!(registry, before);
+
+        registry.set_host("[::1]").unwrap();
+        registry.set_port(None).unwrap();
+        assert_eq!(registry, "[::1]".parse().unwrap());
+
+        registry.set_port(Some(443)).unwrap();
+        assert_eq!(registry, "[::1]:443".parse().unwrap());
+    }
+
+    #[test]
+    #[cfg(feature = "intern")]
+    fn interner() {
+        let mut interner = RegistryInterner::new();
+        let inputs = [
+            "quay.io",
+            "docker.io",
+            "quay.io:443",
+            "quay.io",
+            "docker.io",
+        ];
+
+        let registries = inputs
+            .iter()
+            .map(|s| interner.parse(s).unwrap())
+            .collect::<std::vec::Vec<_>>();
+
+        // Five parses only allocated three registries.
+        assert_eq!(interner.len(), 3);
+        assert!(Arc::ptr_eq(&registries[0], &registries[3]));
+        assert!(Arc::ptr_eq(&registries[1], &registries[4]));
+        assert!(!Arc::ptr_eq(&registries[0], &registries[2]));
+        assert_eq!(registries[2].port, NonZeroU16::new(443));
+
+        assert_eq!(interner.parse("quay-.io"), Err(Error::TrailingDash));
+        assert_eq!(interner.len(), 3);
+    }
+
     #[rstest::rstest]
     #[case("quay.io", Ok(("quay.io", None)))]
     #[case("docker.io", Ok(("docker.io", None)))]
-    #[case("docker.io.", Err(Error::Host))]
+    #[case("docker.io.", Err(Error::EmptyLabel))]
+    #[case("foo..io", Err(Error::EmptyLabel))]
+    #[case("foo!.io", Err(Error::InvalidChar))]
+    #[case("foo-.io", Err(Error::TrailingDash))]
     #[case("foo-bar.io", Ok(("foo-bar.io", None)))]
-    #[case("foo-bar-.io", Err(Error::Host))]
-    #[case("-foo-bar.io", Err(Error::Host))]
+    #[case("foo-bar-.io", Err(Error::TrailingDash))]
+    #[case("-foo-bar.io", Err(Error::LeadingDash))]
+    #[case(
+        "aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa.io",
+        Err(Error::LabelTooLong)
+    )]
     #[case("0zero.io", Ok(("0zero.io", None)))]
     #[case("quay.io:1234", Ok(("quay.io", Some(1234))))]
     #[case("quay.io:0", Err(Error::Port))]
-    #[case("quay.io:", Err(Error::Port))]
+    #[case("quay.io:", Err(Error::PortNotNumeric))]
+    #[case("quay.io:abc", Err(Error::PortNotNumeric))]
+    #[case("quay.io:+443", Err(Error::PortNotNumeric))]
+    #[case("quay.io:65535", Ok(("quay.io", Some(65535))))]
+    #[case("quay.io:65536", Err(Error::PortOverflow))]
+    #[case("quay.io:99999", Err(Error::PortOverflow))]
     #[case(":1234", Err(Error::Host))]
-    #[case(":0", Err(Error::Port))]
+    #[case(":0", Err(Error::Host))]
+    #[case(":abc", Err(Error::Host))]
+    #[case(":", Err(Error::Host))]
+    #[case("[::1]", Ok(("[::1]", None)))]
+    #[case("[::1]:5000", Ok(("[::1]", Some(5000))))]
+    #[case("[fe80::1%eth0]", Ok(("[fe80::1%eth0]", None)))]
+    #[case("[fe80::1%eth0]:5000", Ok(("[fe80::1%eth0]", Some(5000))))]
+    #[case("[fe80::1%]:5000", Err(Error::Zone))]
+    #[case("[fe80::1%eth/0]:5000", Err(Error::Zone))]
+    #[case("[fe80::g]:5000", Err(Error::Ipv6))]
+    #[case("[fe80::1:5000", Err(Error::Ipv6))]
+    #[case("[fe80::1]5000", Err(Error::Ipv6))]
+    #[case("[fe80::1]:0", Err(Error::Port))]
+    #[case("fe80%eth0.io", Err(Error::InvalidChar))]
     fn registry(#[case] input: &str, #[case] result: Result<(&str, Option<u16>), Error>) {
         let result = result.map(|(host, port)| Registry {
-            host: host.into(),
-            port: port.map(|p| NonZeroU16::new(p)).flatten(),
+            host: Cow::Owned(host.into()),
+            port: port.and_then(NonZeroU16::new),
         });
 
         assert_eq!(result, input.parse());
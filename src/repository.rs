@@ -17,51 +17,421 @@
 //! # Ok::<(), oci_imgref::repository::Error>(())
 //! ```
 
+use alloc::borrow::Cow;
+use alloc::format;
 use alloc::string::{String, ToString};
+use alloc::vec::Vec;
 use core::hash::Hash;
+use core::num::NonZeroU16;
+use core::ops::Range;
 use core::{fmt::Display, str::FromStr};
 
 use crate::registry::Registry;
 
 /// an image parsing error
-#[derive(Copy, Clone, Debug, PartialEq, Eq, thiserror::Error, displaydoc::Display)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, thiserror::Error, displaydoc::Display)]
 pub enum Error {
     /// invalid registry: {0}
     Registry(#[from] super::registry::Error),
 
-    /// invalid organization
+    /// invalid organization (expected `/`-separated `[A-Za-z0-9]([A-Za-z0-9._-]*[A-Za-z0-9])?`)
     Organization,
 
-    /// invalid container
+    /// invalid container (expected `[A-Za-z0-9]([A-Za-z0-9._-]*[A-Za-z0-9])?`)
     Container,
+
+    /// repository name longer than 255 characters
+    TooLong,
+
+    /// ambiguous registry: a host without a `.` or a port, other than `localhost`, requires an organization
+    AmbiguousRegistry,
+
+    /// invalid UTF-8
+    Utf8,
 }
 
 /// a container repository reference
-#[derive(Clone, Debug, PartialEq, Eq, Hash)]
-#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
-#[cfg_attr(feature = "serde", serde(try_from = "String", into = "String"))]
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct Repository {
     /// the registry (i.e. `quay.io:1234` in `quay.io:1234/foo/bar:latest`)
     pub registry: Option<Registry>,
 
     /// the organization (i.e. `foo` in `foo/bar:latest`)
-    pub organization: Option<String>,
+    ///
+    /// This may contain several `/`-separated components (i.e. `foo/baz` in
+    /// `quay.io/foo/baz/bar:latest`).
+    pub organization: Option<Cow<'static, str>>,
 
     /// the container (i.e. `bar` in `foo/bar:latest`)
-    pub container: String,
+    pub container: Cow<'static, str>,
+}
+
+impl Repository {
+    /// Assembles a repository from its path segments
+    ///
+    /// The last segment becomes the container and any preceding segments
+    /// form the organization. The result is checked as when parsing, so
+    /// without a registry at most two segments are accepted, and the first
+    /// of two must not read as a host such as `my.app`: either would be
+    /// read back with its first segment as the registry. Likewise a
+    /// single-label registry such as `myhost` needs an organization.
+    ///
+    /// ```rust
+    /// use oci_imgref::repository::Repository;
+    ///
+    /// let registry = "quay.io".parse().ok();
+    /// let repo = Repository::from_segments(registry, ["a", "b", "c"])?;
+    /// assert_eq!(repo.organization.as_deref(), Some("a/b"));
+    /// assert_eq!(repo.container, "c");
+    /// assert_eq!(repo.to_string(), "quay.io/a/b/c");
+    /// # Ok::<(), oci_imgref::repository::Error>(())
+    /// ```
+    pub fn from_segments<'a>(
+        registry: Option<Registry>,
+        segments: impl IntoIterator<Item = &'a str>,
+    ) -> Result<Self, Error> {
+        let repository = assemble(registry, segments)?;
+        check(&repository)?;
+        Ok(repository)
+    }
+
+    /// Starts building a repository
+    ///
+    /// ```rust
+    /// use oci_imgref::registry::Registry;
+    /// use oci_imgref::repository::Repository;
+    ///
+    /// let repo = Repository::builder()
+    ///     .registry(Registry::docker_hub())
+    ///     .organization("library")
+    ///     .container("ubuntu")
+    ///     .build()?;
+    /// assert_eq!(repo.to_string(), "docker.io/library/ubuntu");
+    /// # Ok::<(), oci_imgref::repository::Error>(())
+    /// ```
+    pub const fn builder() -> RepositoryBuilder {
+        RepositoryBuilder {
+            registry: None,
+            organization: None,
+            container: None,
+        }
+    }
+
+    /// Returns a repository borrowing static path segments, without allocating
+    ///
+    /// Being a `const fn`, this suits repositories fixed at compile time. As
+    /// with [`Registry::from_static`], nothing is validated, so the result
+    /// must be one that [`str::parse`] accepts.
+    ///
+    /// ```rust
+    /// use oci_imgref::registry::Registry;
+    /// use oci_imgref::repository::Repository;
+    ///
+    /// const UBUNTU: Repository =
+    ///     Repository::from_static(Some(Registry::docker_hub()), Some("library"), "ubuntu");
+    /// assert_eq!(UBUNTU, "docker.io/library/ubuntu".parse().unwrap());
+    /// ```
+    #[inline]
+    pub const fn from_static(
+        registry: Option<Registry>,
+        organization: Option<&'static str>,
+        container: &'static str,
+    ) -> Self {
+        Self {
+            registry,
+            organization: match organization {
+                Some(organization) => Some(Cow::Borrowed(organization)),
+                None => None,
+            },
+            container: Cow::Borrowed(container),
+        }
+    }
+
+    /// Parses a repository known to name no registry
+    ///
+    /// Unlike [`str::parse`], a first component such as `my.app` that looks
+    /// like a host is taken as the organization. Note that such a repository
+    /// does not round-trip through [`Display`], which writes it back
+    /// ambiguously.
+    ///
+    /// ```rust
+    /// use oci_imgref::repository::Repository;
+    ///
+    /// let repo = Repository::parse_relative("my.app/tool")?;
+    /// assert!(repo.registry.is_none());
+    /// assert_eq!(repo.organization.as_deref(), Some("my.app"));
+    /// assert_eq!(repo.container, "tool");
+    /// # Ok::<(), oci_imgref::repository::Error>(())
+    /// ```
+    pub fn parse_relative(from: &str) -> Result<Self, Error> {
+        if from.len() > crate::MAX_REFERENCE_LEN {
+            return Err(Error::TooLong);
+        }
+
+        let repository = assemble(None, from.split('/'))?;
+        match repository.organization.as_deref() {
+            Some(org) if org.contains('/') => Err(Error::Organization),
+            _ => Ok(repository),
+        }
+    }
+
+    /// Checks the repository against the strict OCI grammar
+    ///
+    /// Parsing accepts any mix of case and separators, as most tools do.
+    /// The distribution specification is narrower: path components are
+    /// lowercase and separated only by `.`, `_`, `__` or runs of `-`. This
+    /// first runs every check of parsing, including the registry host, the
+    /// length limit and that the repository parses back from its string
+    /// form, which is useful for repositories built by hand.
+    ///
+    /// ```rust
+    /// use oci_imgref::repository::{Error, Repository};
+    ///
+    /// let repo: Repository = "quay.io/foo/Bar".parse()?;
+    /// assert_eq!(repo.validate_strict(), Err(Error::Container));
+    /// # Ok::<(), oci_imgref::repository::Error>(())
+    /// ```
+    pub fn validate_strict(&self) -> Result<(), Error> {
+        check(self)?;
+
+        if let Some(org) = &self.organization {
+            org.split('/')
+                .try_for_each(|segment| strict(segment, Error::Organization))?;
+        }
+
+        strict(&self.container, Error::Container)
+    }
+
+    /// Replaces the container after validating it
+    ///
+    /// The whole repository is checked as when parsing, including its
+    /// length. On error the repository is left unchanged.
+    ///
+    /// ```rust
+    /// use oci_imgref::repository::{Error, Repository};
+    ///
+    /// let mut repo: Repository = "quay.io/foo/bar".parse()?;
+    /// repo.set_container("baz")?;
+    /// assert_eq!(repo.to_string(), "quay.io/foo/baz");
+    ///
+    /// assert_eq!(repo.set_container("baz-"), Err(Error::Container));
+    /// assert_eq!(repo.container, "baz");
+    /// # Ok::<(), oci_imgref::repository::Error>(())
+    /// ```
+    pub fn set_container(&mut self, container: &str) -> Result<(), Error> {
+        let container = Cow::Owned(container.into());
+        let previous = core::mem::replace(&mut self.container, container);
+
+        if let Err(e) = check(self) {
+            self.container = previous;
+            return Err(e);
+        }
+
+        Ok(())
+    }
+
+    /// Replaces the organization after validating it
+    ///
+    /// As when parsing, an organization of several segments, or one that
+    /// reads as a host such as `my.app`, requires a registry. On error the
+    /// repository is left unchanged.
+    pub fn set_organization(&mut self, org: Option<&str>) -> Result<(), Error> {
+        let org = org.map(|org| Cow::Owned(org.into()));
+        let previous = core::mem::replace(&mut self.organization, org);
+
+        if let Err(e) = check(self) {
+            self.organization = previous;
+            return Err(e);
+        }
+
+        Ok(())
+    }
+
+    /// Returns the length of the repository as written by [`Display`]
+    #[allow(clippy::len_without_is_empty)] // repositories are never empty
+    pub fn len(&self) -> usize {
+        let registry = self.registry.as_ref().map_or(0, |r| r.len() + 1);
+        let organization = self.organization.as_ref().map_or(0, |o| o.len() + 1);
+        registry + organization + self.container.len()
+    }
+
+    /// Displays the repository, optionally leaving out the registry
+    ///
+    /// ```rust
+    /// use oci_imgref::repository::Repository;
+    ///
+    /// let repo: Repository = "quay.io/foo/bar".parse().unwrap();
+    /// assert_eq!(repo.display_with(true).to_string(), "quay.io/foo/bar");
+    /// assert_eq!(repo.display_with(false).to_string(), "foo/bar");
+    /// ```
+    #[inline]
+    pub fn display_with(&self, include_registry: bool) -> impl Display + '_ {
+        DisplayWith {
+            repository: self,
+            registry: include_registry,
+        }
+    }
+
+    /// Iterates over the path segments of the repository
+    ///
+    /// The organization is stored as a single string, so the segments are
+    /// borrowed from it without allocating. The container is always last.
+    ///
+    /// ```rust
+    /// use oci_imgref::repository::Repository;
+    ///
+    /// let repo: Repository = "gcr.io/project/team/app".parse().unwrap();
+    /// assert!(repo.segments().eq(["project", "team", "app"]));
+    /// ```
+    pub fn segments(&self) -> impl DoubleEndedIterator<Item = &str> {
+        self.organization
+            .as_deref()
+            .into_iter()
+            .flat_map(|org| org.split('/'))
+            .chain(core::iter::once(&*self.container))
+    }
+
+    /// Returns the number of path segments, including the container
+    ///
+    /// A bare container has depth 1 and `org/container` has depth 2. The
+    /// registry is not counted. This lets policies enforce a maximum or
+    /// exact depth, such as exactly one organization level.
+    ///
+    /// ```rust
+    /// use oci_imgref::repository::Repository;
+    ///
+    /// let repo: Repository = "gcr.io/project/team/app".parse().unwrap();
+    /// assert_eq!(repo.depth(), 3);
+    /// ```
+    #[inline]
+    pub fn depth(&self) -> usize {
+        self.segments().count()
+    }
+
+    /// Returns the repository name as used in registry API paths
+    ///
+    /// This is the full path without the registry, as in
+    /// `/v2/<name>/manifests/<reference>`. The path grammar only admits
+    /// URL-safe characters, so no escaping is applied.
+    ///
+    /// ```rust
+    /// use oci_imgref::repository::Repository;
+    ///
+    /// let repo: Repository = "docker.io/library/ubuntu".parse().unwrap();
+    /// assert_eq!(repo.to_registry_path(), "library/ubuntu");
+    /// ```
+    pub fn to_registry_path(&self) -> String {
+        match &self.organization {
+            Some(org) => format!("{}/{}", org, self.container),
+            None => self.container.to_string(),
+        }
+    }
+
+    /// Returns the registry for editing in place
+    ///
+    /// ```rust
+    /// use oci_imgref::repository::Repository;
+    ///
+    /// let mut repo: Repository = "quay.io/foo/bar".parse().unwrap();
+    /// *repo.registry_mut() = "ghcr.io".parse().ok();
+    /// assert_eq!(repo.to_string(), "ghcr.io/foo/bar");
+    /// ```
+    #[inline]
+    pub fn registry_mut(&mut self) -> &mut Option<Registry> {
+        &mut self.registry
+    }
+
+    /// Returns the registry, discarding the organization and container
+    ///
+    /// ```rust
+    /// use oci_imgref::repository::Repository;
+    ///
+    /// let repo: Repository = "quay.io:5000/foo/bar".parse().unwrap();
+    /// assert_eq!(repo.into_registry().unwrap().to_string(), "quay.io:5000");
+    /// ```
+    #[inline]
+    pub fn into_registry(self) -> Option<Registry> {
+        self.registry
+    }
+}
+
+/// a builder for [`Repository`], created by [`Repository::builder`]
+#[derive(Clone, Debug, Default)]
+#[must_use]
+pub struct RepositoryBuilder {
+    registry: Option<Registry>,
+    organization: Option<String>,
+    container: Option<String>,
+}
+
+impl RepositoryBuilder {
+    /// Sets the registry
+    pub fn registry(mut self, registry: Registry) -> Self {
+        self.registry = Some(registry);
+        self
+    }
+
+    /// Sets the organization, which may have several `/`-separated segments
+    pub fn organization(mut self, organization: &str) -> Self {
+        self.organization = Some(organization.into());
+        self
+    }
+
+    /// Sets the container
+    pub fn container(mut self, container: &str) -> Self {
+        self.container = Some(container.into());
+        self
+    }
+
+    /// Validates the components and returns the repository
+    ///
+    /// The assembled repository is checked as when parsing, so it always
+    /// displays as a string that parses back to it. A missing container is
+    /// reported as [`Error::Container`].
+    pub fn build(self) -> Result<Repository, Error> {
+        let repository = Repository {
+            registry: self.registry,
+            organization: self.organization.map(Cow::Owned),
+            container: Cow::Owned(self.container.ok_or(Error::Container)?),
+        };
+
+        check(&repository)?;
+        Ok(repository)
+    }
 }
 
 impl Display for Repository {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
-        if let Some(registry) = &self.registry {
+        self.display_with(true).fmt(f)
+    }
+}
+
+/// a repository displayed with or without its registry
+struct DisplayWith<'a> {
+    repository: &'a Repository,
+    registry: bool,
+}
+
+impl Display for DisplayWith<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let repo = self.repository;
+
+        if let Some(registry) = repo.registry.as_ref().filter(|_| self.registry) {
             write!(f, "{}/", registry)?;
         }
 
-        if let Some(org) = &self.organization {
+        if let Some(org) = &repo.organization {
             write!(f, "{}/", &org)?;
         }
 
-        write!(f, "{}", self.container)
+        write!(f, "{}", repo.container)
+    }
+}
+
+impl AsRef<Self> for Repository {
+    #[inline(always)]
+    fn as_ref(&self) -> &Self {
+        self
     }
 }
 
@@ -76,42 +446,16 @@ impl FromStr for Repository {
     type Err = Error;
 
     fn from_str(from: &str) -> Result<Self, Self::Err> {
-        match from.rsplit_once('/') {
-            // `ubuntu`
-            None => Ok(Self {
-                registry: None,
-                organization: None,
-                container: path(from, Error::Container)?.into(),
-            }),
-
-            Some((pfx, con)) => match pfx.rsplit_once('/') {
-                None => {
-                    // `quay.io/ubuntu`
-                    if pfx == "localhost" || pfx.contains('.') || pfx.contains(':') {
-                        Ok(Self {
-                            registry: Some(pfx.parse()?),
-                            organization: None,
-                            container: path(con, Error::Container)?,
-                        })
-
-                    // `library/ubuntu`
-                    } else {
-                        Ok(Self {
-                            registry: None,
-                            organization: Some(path(pfx, Error::Organization)?),
-                            container: path(con, Error::Container)?,
-                        })
-                    }
-                }
+        let parts = split(from)?;
 
-                // `docker.io/library/ubuntu`
-                Some((reg, org)) => Ok(Self {
-                    registry: Some(reg.parse()?),
-                    organization: Some(path(org, Error::Organization)?),
-                    container: path(con, Error::Container)?,
-                }),
-            },
-        }
+        Ok(Self {
+            registry: parts.registry.map(|(host, port)| Registry {
+                host: Cow::Owned(host.into()),
+                port,
+            }),
+            organization: parts.organization.map(|org| Cow::Owned(org.into())),
+            container: Cow::Owned(parts.container.into()),
+        })
     }
 }
 
@@ -124,8 +468,373 @@ impl TryFrom<String> for Repository {
     }
 }
 
+impl TryFrom<&str> for Repository {
+    type Error = Error;
+
+    #[inline]
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl TryFrom<&[u8]> for Repository {
+    type Error = Error;
+
+    #[inline]
+    fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
+        core::str::from_utf8(value).or(Err(Error::Utf8))?.parse()
+    }
+}
+
+/// a repository stored inline, parsed without allocating
+///
+/// The repository is kept as written in a fixed buffer of
+/// [`MAX_REFERENCE_LEN`](crate::MAX_REFERENCE_LEN) bytes, together with the
+/// positions of its components. It is validated exactly as [`Repository`]
+/// is, and converts into one when owned components are needed.
+///
+/// ```rust
+/// use oci_imgref::repository::{InlineRepository, Repository};
+///
+/// let repo: InlineRepository = "gcr.io/project/team/app".parse()?;
+/// assert_eq!(repo.registry(), Some("gcr.io"));
+/// assert_eq!(repo.organization(), Some("project/team"));
+/// assert!(repo.segments().eq(["project", "team", "app"]));
+///
+/// let owned: Repository = repo.to_repository();
+/// assert_eq!(owned, "gcr.io/project/team/app".parse()?);
+/// # Ok::<(), oci_imgref::repository::Error>(())
+/// ```
+#[cfg(feature = "inline")]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct InlineRepository {
+    text: heapless::String<{ crate::MAX_REFERENCE_LEN }>,
+
+    // the end of the registry host, and its port
+    host: Option<(usize, Option<NonZeroU16>)>,
+
+    // the start of the organization, or of the container without one
+    path: usize,
+
+    // the start of the container
+    container: usize,
+}
+
+#[cfg(feature = "inline")]
+impl InlineRepository {
+    /// Returns the repository as written
+    #[inline]
+    pub fn as_str(&self) -> &str {
+        &self.text
+    }
+
+    /// Returns the registry, including any port
+    #[inline]
+    pub fn registry(&self) -> Option<&str> {
+        self.host.map(|_| &self.text[..self.path - 1])
+    }
+
+    /// Returns the organization
+    #[inline]
+    pub fn organization(&self) -> Option<&str> {
+        match self.container > self.path {
+            true => Some(&self.text[self.path..self.container - 1]),
+            false => None,
+        }
+    }
+
+    /// Returns the container
+    #[inline]
+    pub fn container(&self) -> &str {
+        &self.text[self.container..]
+    }
+
+    /// Iterates over the path segments of the repository
+    ///
+    /// See [`Repository::segments`].
+    pub fn segments(&self) -> impl DoubleEndedIterator<Item = &str> {
+        self.organization()
+            .into_iter()
+            .flat_map(|org| org.split('/'))
+            .chain(core::iter::once(self.container()))
+    }
+
+    /// Returns the repository with owned components
+    pub fn to_repository(&self) -> Repository {
+        Repository {
+            registry: self.host.map(|(end, port)| Registry {
+                host: Cow::Owned(self.text[..end].into()),
+                port,
+            }),
+            organization: self.organization().map(|org| Cow::Owned(org.into())),
+            container: Cow::Owned(self.container().into()),
+        }
+    }
+}
+
+#[cfg(feature = "inline")]
+impl Display for InlineRepository {
+    #[inline]
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[cfg(feature = "inline")]
+impl FromStr for InlineRepository {
+    type Err = Error;
+
+    fn from_str(from: &str) -> Result<Self, Self::Err> {
+        let parts = split(from)?;
+
+        // The length was checked against the buffer size while splitting.
+        let mut text = heapless::String::new();
+        text.push_str(from).or(Err(Error::TooLong))?;
+
+        let container = from.len() - parts.container.len();
+        let path = parts
+            .organization
+            .map_or(container, |org| container - 1 - org.len());
+
+        Ok(Self {
+            text,
+            host: parts.registry.map(|(host, port)| (host.len(), port)),
+            path,
+            container,
+        })
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for Repository {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            serializer.collect_str(self)
+        } else {
+            (&self.registry, &self.organization, &self.container).serialize(serializer)
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Repository {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        use serde::de::Error as _;
+
+        if deserializer.is_human_readable() {
+            let value = String::deserialize(deserializer)?;
+            value.parse().map_err(D::Error::custom)
+        } else {
+            let (registry, organization, container) =
+                <(Option<Registry>, Option<String>, String)>::deserialize(deserializer)?;
+
+            let repository = Self {
+                registry,
+                organization: organization.map(Cow::Owned),
+                container: Cow::Owned(container),
+            };
+
+            check(&repository).map_err(D::Error::custom)?;
+            Ok(repository)
+        }
+    }
+}
+
+/// the validated components of a repository, borrowed from the input
+#[derive(Copy, Clone)]
+pub(crate) struct Parts<'a> {
+    pub(crate) registry: Option<(&'a str, Option<NonZeroU16>)>,
+    pub(crate) organization: Option<&'a str>,
+    pub(crate) container: &'a str,
+}
+
+/// the slash positions of a repository, gathered one byte at a time
+#[derive(Copy, Clone, Default)]
+pub(crate) struct Slashes {
+    first: Option<usize>,
+    last: Option<usize>,
+
+    // whether a `.` or `:` precedes the first slash
+    domain: bool,
+}
+
+impl Slashes {
+    /// Records the byte `b` found at offset `i`
+    #[inline(always)]
+    pub(crate) fn push(&mut self, i: usize, b: u8) {
+        match b {
+            b'/' => {
+                self.first = self.first.or(Some(i));
+                self.last = Some(i);
+            }
+
+            b'.' | b':' if self.first.is_none() => self.domain = true,
+            _ => (),
+        }
+    }
+}
+
+/// Splits a repository into its validated components without allocating
+#[inline]
+pub(crate) fn split(from: &str) -> Result<Parts<'_>, Error> {
+    let mut slashes = Slashes::default();
+    for (i, b) in from.bytes().enumerate() {
+        slashes.push(i, b);
+    }
+
+    split_at(from, slashes)
+}
+
+/// Validates each component of a repository, collecting every error
+///
+/// Each error comes with the range of the component it applies to, in
+/// input order.
+pub(crate) fn check_all(from: &str, slashes: Slashes) -> Vec<(Error, Range<usize>)> {
+    if from.len() > crate::MAX_REFERENCE_LEN {
+        return Vec::from([(Error::TooLong, 0..from.len())]);
+    }
+
+    let (reg, org, con) = locate(from, slashes);
+    let start = from.len() - con.len();
+    let mut errors = Vec::new();
+
+    if let Some(reg) = reg {
+        if let Err(e) = crate::registry::split(reg) {
+            errors.push((e.into(), 0..reg.len()));
+        }
+    }
+
+    if let Some(org) = org {
+        if let Err(e) = organization(org) {
+            errors.push((e, start - 1 - org.len()..start - 1));
+        }
+    }
+
+    if let Err(e) = path(con, Error::Container) {
+        errors.push((e, start..from.len()));
+    }
+
+    errors
+}
+
+/// Locates the registry, organization and container without validating them
+fn locate(from: &str, slashes: Slashes) -> (Option<&str>, Option<&str>, &str) {
+    match (slashes.first, slashes.last) {
+        // `docker.io/library/ubuntu` or `gcr.io/project/team/app`
+        (Some(first), Some(last)) if first < last => (
+            Some(&from[..first]),
+            Some(&from[first + 1..last]),
+            &from[last + 1..],
+        ),
+
+        // `quay.io/ubuntu`
+        (Some(first), _) if slashes.domain || &from[..first] == "localhost" => {
+            (Some(&from[..first]), None, &from[first + 1..])
+        }
+
+        // `library/ubuntu`
+        (Some(first), _) => (None, Some(&from[..first]), &from[first + 1..]),
+
+        // `ubuntu`
+        (None, _) => (None, None, from),
+    }
+}
+
+/// Splits a repository at slash positions gathered beforehand
+pub(crate) fn split_at(from: &str, slashes: Slashes) -> Result<Parts<'_>, Error> {
+    if from.len() > crate::MAX_REFERENCE_LEN {
+        return Err(Error::TooLong);
+    }
+
+    let (reg, org, con) = locate(from, slashes);
+
+    // Components are checked left to right, so the first error is reported.
+    let registry = reg.map(crate::registry::split).transpose()?;
+
+    if let Some(org) = org {
+        organization(org)?;
+    }
+
+    path(con, Error::Container)?;
+
+    Ok(Parts {
+        registry,
+        organization: org,
+        container: con,
+    })
+}
+
+/// Assembles a repository from its path segments, validating each
+fn assemble<'a>(
+    registry: Option<Registry>,
+    segments: impl IntoIterator<Item = &'a str>,
+) -> Result<Repository, Error> {
+    let mut organization: Option<String> = None;
+    let mut container: Option<&str> = None;
+
+    for segment in segments {
+        if let Some(prev) = container.replace(segment) {
+            path(prev, Error::Organization)?;
+
+            match &mut organization {
+                None => organization = Some(prev.into()),
+                Some(org) => {
+                    org.push('/');
+                    org.push_str(prev);
+                }
+            }
+        }
+    }
+
+    let container = container.ok_or(Error::Container)?;
+    path(container, Error::Container)?;
+
+    Ok(Repository {
+        registry,
+        organization: organization.map(Cow::Owned),
+        container: Cow::Owned(container.into()),
+    })
+}
+
+/// Checks that a repository displays as a string that parses back to it
+pub(crate) fn check(repo: &Repository) -> Result<(), Error> {
+    if let Some(registry) = &repo.registry {
+        crate::registry::validate(&registry.host)?;
+
+        // Without an organization, a single-label host would read as one.
+        let host = &registry.host;
+        let qualified = host.contains(['.', '[']) || registry.port.is_some() || host == "localhost";
+        if repo.organization.is_none() && !qualified {
+            return Err(Error::AmbiguousRegistry);
+        }
+    }
+
+    if let Some(org) = &repo.organization {
+        organization(org)?;
+
+        // Without a registry, the first segment must not read as one.
+        let hostlike = org.contains('.') || org == "localhost";
+        if repo.registry.is_none() && (org.contains('/') || hostlike) {
+            return Err(Error::Organization);
+        }
+    }
+
+    path(&repo.container, Error::Container)?;
+
+    match repo.len() > crate::MAX_REFERENCE_LEN {
+        true => Err(Error::TooLong),
+        false => Ok(()),
+    }
+}
+
+#[inline(always)]
+fn organization(from: &str) -> Result<(), Error> {
+    from.split('/')
+        .try_for_each(|segment| path(segment, Error::Organization))
+}
+
 #[inline(always)]
-fn path(from: &str, err: Error) -> Result<String, Error> {
+fn path(from: &str, err: Error) -> Result<(), Error> {
     for c in from.chars() {
         match c {
             'a'..='z' | 'A'..='Z' | '0'..='9' | '_' | '.' | '-' => (),
@@ -133,12 +842,25 @@ fn path(from: &str, err: Error) -> Result<String, Error> {
         }
     }
 
-    match (from.chars().next(), from.chars().rev().next()) {
+    match (from.chars().next(), from.chars().next_back()) {
         (Some(l), Some(r)) if l.is_ascii_alphanumeric() && r.is_ascii_alphanumeric() => (),
         _ => return Err(err),
     }
 
-    Ok(from.into())
+    Ok(())
+}
+
+/// Checks a path component against `[a-z0-9]+((\.|_|__|-+)[a-z0-9]+)*`
+fn strict(from: &str, err: Error) -> Result<(), Error> {
+    let mut separators = from.split(|c: char| c.is_ascii_lowercase() || c.is_ascii_digit());
+
+    let edges = (separators.next(), separators.next_back());
+    let valid = separators.all(|s| matches!(s, "." | "_" | "__") || s.bytes().all(|b| b == b'-'));
+
+    match (edges, valid) {
+        ((Some(""), Some("")), true) => Ok(()),
+        _ => Err(err),
+    }
 }
 
 #[cfg(test)]
@@ -161,4 +883,264 @@ mod tests {
         assert!(path(".invalid", Error::Container).is_err()); // Bad start with dot
         assert!(path("_invalid", Error::Container).is_err()); // Bad start with underscore
     }
+
+    #[rstest::rstest]
+    #[case("ubuntu", "ubuntu")]
+    #[case("quay.io/ubuntu", "ubuntu")]
+    #[case("library/ubuntu", "library/ubuntu")]
+    #[case("docker.io/library/ubuntu", "library/ubuntu")]
+    #[case("gcr.io/project/team/app", "project/team/app")]
+    fn test_to_registry_path(#[case] input: &str, #[case] path: &str) {
+        let repo: Repository = input.parse().unwrap();
+        assert_eq!(repo.to_registry_path(), path);
+    }
+
+    #[test]
+    fn test_from_segments() {
+        let registry: Registry = "quay.io".parse().unwrap();
+
+        let repo = Repository::from_segments(Some(registry.clone()), ["a", "b", "c"]).unwrap();
+        assert_eq!(repo.registry, Some(registry.clone()));
+        assert_eq!(repo.organization.as_deref(), Some("a/b"));
+        assert_eq!(repo.container, "c");
+        assert_eq!(repo, "quay.io/a/b/c".parse().unwrap());
+
+        let repo = Repository::from_segments(None, ["a", "b"]).unwrap();
+        assert_eq!(repo, "a/b".parse().unwrap());
+
+        let empty: [&str; 0] = [];
+        assert_eq!(
+            Repository::from_segments(None, empty),
+            Err(Error::Container)
+        );
+        assert_eq!(
+            Repository::from_segments(None, ["a", "b", "c"]),
+            Err(Error::Organization)
+        );
+        assert_eq!(
+            Repository::from_segments(None, ["a-", "b"]),
+            Err(Error::Organization)
+        );
+        assert_eq!(
+            Repository::from_segments(None, ["a", "b-"]),
+            Err(Error::Container)
+        );
+
+        // These would be read back with a registry.
+        for host in ["my.app", "localhost"] {
+            assert_eq!(
+                Repository::from_segments(None, [host, "x"]),
+                Err(Error::Organization)
+            );
+        }
+
+        assert_eq!(
+            Repository::from_segments(None, ["host:5000", "x"]),
+            Err(Error::Organization)
+        );
+
+        assert_eq!(
+            Repository::from_segments("myhost".parse().ok(), ["x"]),
+            Err(Error::AmbiguousRegistry)
+        );
+
+        let long = "a".repeat(crate::MAX_REFERENCE_LEN);
+        assert_eq!(
+            Repository::from_segments(Some(registry), ["a", &long]),
+            Err(Error::TooLong)
+        );
+    }
+
+    #[rstest::rstest]
+    #[case("tool", Ok((None, "tool")))]
+    #[case("my.app/tool", Ok((Some("my.app"), "tool")))]
+    #[case("localhost:5000/tool", Err(Error::Organization))]
+    #[case("a/b/c", Err(Error::Organization))]
+    #[case("my.app/", Err(Error::Container))]
+    fn test_parse_relative(
+        #[case] input: &str,
+        #[case] expected: Result<(Option<&str>, &str), Error>,
+    ) {
+        let repo = Repository::parse_relative(input);
+        assert!(repo.as_ref().map_or(true, |r| r.registry.is_none()));
+
+        let repo = repo.map(|r| (r.organization, r.container));
+        assert_eq!(repo, expected.map(|(o, c)| (o.map(Into::into), c.into())));
+    }
+
+    #[rstest::rstest]
+    #[case("quay.io/foo/bar", "quay.io/foo/bar", "foo/bar")]
+    #[case("localhost:5000/bar", "localhost:5000/bar", "bar")]
+    #[case("foo/bar", "foo/bar", "foo/bar")]
+    #[case("bar", "bar", "bar")]
+    fn test_display_with(#[case] input: &str, #[case] with: &str, #[case] without: &str) {
+        let repo: Repository = input.parse().unwrap();
+        assert_eq!(repo.display_with(true).to_string(), with);
+        assert_eq!(repo.display_with(false).to_string(), without);
+    }
+
+    #[rstest::rstest]
+    #[case("bar", 1)]
+    #[case("localhost:5000/bar", 1)]
+    #[case("foo/bar", 2)]
+    #[case("quay.io/foo/bar", 2)]
+    #[case("gcr.io/project/team/sub/app", 4)]
+    fn test_depth(#[case] input: &str, #[case] expected: usize) {
+        let repo: Repository = input.parse().unwrap();
+        assert_eq!(repo.depth(), expected);
+    }
+
+    #[rstest::rstest]
+    #[case("a", true)]
+    #[case("a0.b_c__d-e---f", true)]
+    #[case("", false)]
+    #[case("A", false)]
+    #[case("a..b", false)]
+    #[case("a___b", false)]
+    #[case("a._b", false)]
+    #[case("a-", false)]
+    #[case("_a", false)]
+    fn test_strict(#[case] input: &str, #[case] valid: bool) {
+        assert_eq!(strict(input, Error::Container).is_ok(), valid);
+    }
+
+    #[test]
+    fn test_builder() {
+        let registry: Registry = "gcr.io".parse().unwrap();
+
+        let repo = Repository::builder()
+            .registry(registry.clone())
+            .organization("project/team")
+            .container("app")
+            .build();
+        assert_eq!(repo, "gcr.io/project/team/app".parse());
+
+        let repo = Repository::builder().container("app").build();
+        assert_eq!(repo, "app".parse());
+
+        let builder = Repository::builder()
+            .registry(registry)
+            .organization("project");
+        assert_eq!(builder.clone().build(), Err(Error::Container));
+        assert_eq!(
+            builder.clone().container("app-").build(),
+            Err(Error::Container)
+        );
+        assert_eq!(
+            builder.organization("project/").container("app").build(),
+            Err(Error::Organization)
+        );
+        assert_eq!(
+            Repository::builder()
+                .organization("a/b")
+                .container("c")
+                .build(),
+            Err(Error::Organization)
+        );
+        assert_eq!(
+            Repository::builder()
+                .organization("my.app")
+                .container("x")
+                .build(),
+            Err(Error::Organization)
+        );
+
+        // `myhost/app` would be read back with `myhost` as the organization.
+        let myhost = "myhost".parse::<Registry>().unwrap();
+        assert_eq!(
+            Repository::builder()
+                .registry(myhost.clone())
+                .container("app")
+                .build(),
+            Err(Error::AmbiguousRegistry)
+        );
+
+        for registry in [myhost, "myhost:5000".parse().unwrap()] {
+            let repo = Repository::builder()
+                .registry(registry)
+                .organization("org")
+                .container("app")
+                .build()
+                .unwrap();
+            assert_eq!(repo.to_string().parse(), Ok(repo));
+        }
+
+        let repo = Repository::builder()
+            .registry("myhost:5000".parse().unwrap())
+            .container("app")
+            .build()
+            .unwrap();
+        assert_eq!(repo.to_string().parse(), Ok(repo));
+    }
+
+    #[test]
+    fn test_setters() {
+        let mut repo: Repository = "library/ubuntu".parse().unwrap();
+        let before = repo.clone();
+
+        assert_eq!(repo.set_container("ubuntu/"), Err(Error::Container));
+        assert_eq!(
+            repo.set_organization(Some("a//b")),
+            Err(Error::Organization)
+        );
+        assert_eq!(repo.set_organization(Some("a/b")), Err(Error::Organization));
+        assert_eq!(
+            repo.set_organization(Some("my.app")),
+            Err(Error::Organization)
+        );
+        assert_eq!(
+            repo.set_organization(Some("localhost")),
+            Err(Error::Organization)
+        );
+        assert_eq!(repo, before);
+
+        let mut long: Repository = "quay.io/foo/bar".parse().unwrap();
+        assert_eq!(long.set_container(&"a".repeat(300)), Err(Error::TooLong));
+        assert_eq!(long, "quay.io/foo/bar".parse().unwrap());
+
+        repo.set_container("debian").unwrap();
+        repo.set_organization(None).unwrap();
+        assert_eq!(repo, "debian".parse().unwrap());
+
+        repo.registry = "quay.io".parse().ok();
+        repo.set_organization(Some("a/b")).unwrap();
+        assert_eq!(repo, "quay.io/a/b/debian".parse().unwrap());
+
+        repo.set_organization(Some("my.app")).unwrap();
+        assert_eq!(repo, "quay.io/my.app/debian".parse().unwrap());
+    }
+
+    #[rstest::rstest]
+    #[case("quay.io:5000/foo/bar", Some("quay.io:5000"))]
+    #[case("[::1]/foo", Some("[::1]"))]
+    #[case("foo/bar", None)]
+    #[case("bar", None)]
+    fn into_registry(#[case] input: &str, #[case] expected: Option<&str>) {
+        let repo: Repository = input.parse().unwrap();
+        let registry = repo.registry.clone();
+
+        assert_eq!(repo.clone().into_registry(), registry);
+        assert_eq!(repo.into_registry().map(String::from).as_deref(), expected);
+    }
+
+    #[test]
+    fn registry_mut() {
+        let mut repo: Repository = "quay.io:5000/foo/bar".parse().unwrap();
+
+        repo.registry_mut()
+            .as_mut()
+            .unwrap()
+            .set_port(None)
+            .unwrap();
+        assert_eq!(repo.to_string(), "quay.io/foo/bar");
+
+        *repo.registry_mut() = None;
+        assert_eq!(repo, "foo/bar".parse().unwrap());
+
+        repo.registry_mut()
+            .get_or_insert_with(Registry::docker_hub)
+            .set_port(Some(443))
+            .unwrap();
+        assert_eq!(repo, "docker.io:443/foo/bar".parse().unwrap());
+    }
 }
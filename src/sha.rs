@@ -0,0 +1,53 @@
+//! Computing digests of content
+//!
+//! # Examples
+//!
+//! ```rust
+//! use oci_imgref::digest::Digest;
+//! use oci_imgref::image::Image;
+//! use oci_imgref::sha::DigestExt;
+//!
+//! let manifest = b"";
+//! let image: Image = "quay.io/foo/bar:latest".parse()?;
+//! let image = image.pin(Digest::sha256(manifest)?);
+//!
+//! let digest = "sha256:e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855";
+//! assert_eq!(image.to_string(), format!("quay.io/foo/bar:latest@{digest}"));
+//! # Ok::<(), oci_imgref::image::Error>(())
+//! ```
+
+use alloc::string::String;
+
+use sha2::Digest as _;
+
+use crate::digest::{Digest, Error};
+
+/// computing digests of content
+///
+/// This is implemented for [`Digest`], so that with the trait in scope a
+/// digest is computed with `Digest::sha256(data)`.
+pub trait DigestExt: Sized {
+    /// Computes the SHA-256 digest of `data`
+    ///
+    /// The computed value is always well-formed, but a [`Digest`] can only be
+    /// built by parsing, so the parse error is passed through rather than
+    /// unwrapped.
+    fn sha256(data: &[u8]) -> Result<Self, Error>;
+}
+
+impl DigestExt for Digest {
+    fn sha256(data: &[u8]) -> Result<Self, Error> {
+        const HEX: &[u8; 16] = b"0123456789abcdef";
+
+        let hash = sha2::Sha256::digest(data);
+        let mut digest = String::with_capacity(7 + hash.len() * 2);
+        digest.push_str("sha256:");
+
+        for byte in hash {
+            digest.push(char::from(HEX[usize::from(byte >> 4)]));
+            digest.push(char::from(HEX[usize::from(byte & 0xf)]));
+        }
+
+        digest.parse()
+    }
+}
@@ -0,0 +1,164 @@
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+use oci_imgref::repository::Repository;
+
+struct Counter;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for Counter {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|n| n.set(n.get() + 1));
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Counter = Counter;
+
+fn allocations<T>(f: impl FnOnce() -> T) -> (T, usize) {
+    let before = ALLOCATIONS.with(Cell::get);
+    let value = f();
+    (value, ALLOCATIONS.with(Cell::get) - before)
+}
+
+// Each parsed component is owned, so it takes one buffer.
+#[rstest::rstest]
+#[case("ubuntu", 1)]
+#[case("library/ubuntu", 2)]
+#[case("gcr.io/project/team/app", 3)]
+fn repository(#[case] input: &str, #[case] most: usize) {
+    let (repo, count) = allocations(|| input.parse::<Repository>().unwrap());
+    assert!(
+        count <= most,
+        "{count} allocations, expected at most {most}"
+    );
+
+    let (segments, count) = allocations(|| repo.segments().count());
+    assert_eq!(
+        segments,
+        input.split('/').count() - usize::from(repo.registry.is_some())
+    );
+    assert_eq!(count, 0);
+}
+
+#[cfg(feature = "inline")]
+#[rstest::rstest]
+#[case("library/ubuntu")]
+#[case("quay.io:5000/foo/bar")]
+#[case("gcr.io/project/team/app")]
+fn inline_repository(#[case] input: &str) {
+    use oci_imgref::repository::InlineRepository;
+
+    let (repo, count) = allocations(|| input.parse::<InlineRepository>().unwrap());
+    assert_eq!(count, 0);
+
+    let (segments, count) = allocations(|| repo.segments().count());
+    assert_eq!(segments, repo.to_repository().segments().count());
+    assert_eq!(count, 0);
+
+    assert_eq!(repo.to_string(), input);
+    assert_eq!(repo.to_repository(), input.parse().unwrap());
+}
+
+#[rstest::rstest]
+#[case("ubuntu")]
+#[case("quay.io:5000/foo/bar:latest")]
+#[case("foo@sha256:e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855")]
+#[case("foo@sha256:e3b0")]
+#[case("foo@sha257:e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855")]
+fn parse_ranges(#[case] input: &str) {
+    use oci_imgref::image::Image;
+
+    let (ranges, count) = allocations(|| oci_imgref::image::parse_ranges(input));
+    assert_eq!(ranges.map(|_| ()), input.parse::<Image>().map(|_| ()));
+    assert_eq!(count, 0);
+}
+
+#[test]
+fn algorithm() {
+    use oci_imgref::digest::Digest;
+    use oci_imgref::image::Algorithm;
+
+    let digest: Digest = "sha256:e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        .parse()
+        .unwrap();
+
+    let (algorithm, count) = allocations(|| Algorithm::of(&digest));
+    assert_eq!(algorithm, Some(Algorithm::Sha256));
+    assert_eq!(count, 0);
+}
+
+#[test]
+fn ordering() {
+    use oci_imgref::image::Image;
+
+    let a: Image = "foo:v1@sha256:e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        .parse()
+        .unwrap();
+    let b: Image = "foo:v1@sha512:00000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000"
+        .parse()
+        .unwrap();
+
+    let (ordering, count) = allocations(|| (a.cmp(&b), a.cmp(&a)));
+    assert_eq!(
+        ordering,
+        (core::cmp::Ordering::Less, core::cmp::Ordering::Equal)
+    );
+    assert_eq!(count, 0);
+}
+
+#[test]
+fn static_registry() {
+    use oci_imgref::registry::Registry;
+
+    let (hub, count) = allocations(Registry::docker_hub);
+    assert_eq!(count, 0);
+
+    let (mirror, count) = allocations(|| Registry::from_static("mirror.example.com", None));
+    assert_eq!(count, 0);
+
+    assert_eq!(hub, "docker.io".parse().unwrap());
+    assert_eq!(mirror, "mirror.example.com".parse().unwrap());
+}
+
+#[test]
+#[cfg(feature = "intern")]
+fn registry_interner() {
+    use oci_imgref::registry::{Registry, RegistryInterner};
+    use std::sync::Arc;
+
+    let mut interner = RegistryInterner::new();
+    let (first, count) = allocations(|| interner.parse("quay.io:5000").unwrap());
+    assert!(count > 0);
+
+    // Repeated registries are shared rather than allocated again.
+    let (repeated, count) = allocations(|| {
+        (0..100)
+            .map(|_| interner.parse("quay.io:5000").unwrap())
+            .all(|registry| Arc::ptr_eq(&registry, &first))
+    });
+    assert!(repeated);
+    assert_eq!(count, 0);
+
+    let (_, count) = allocations(|| "quay.io:5000".parse::<Registry>().unwrap());
+    assert!(count > 0);
+}
+
+#[test]
+fn static_repository() {
+    use oci_imgref::registry::Registry;
+
+    let (repo, count) = allocations(|| {
+        Repository::from_static(Some(Registry::docker_hub()), Some("library"), "ubuntu")
+    });
+    assert_eq!(count, 0);
+    assert_eq!(repo, "docker.io/library/ubuntu".parse().unwrap());
+}
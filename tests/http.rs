@@ -0,0 +1,26 @@
+#![cfg(feature = "http")]
+
+use oci_imgref::image::{Error, Image};
+
+#[rstest::rstest]
+#[case(
+    "quay.io/foo/bar:latest",
+    "https://quay.io:443/v2/foo/bar/manifests/latest"
+)]
+#[case("localhost:5000/bar", "https://localhost:5000/v2/bar/manifests/latest")]
+#[case(
+    "[::1]:5000/foo/bar@sha256:e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855",
+    "https://[::1]:5000/v2/foo/bar/manifests/sha256:e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+)]
+fn uri(#[case] input: &str, #[case] expected: &str) {
+    let image: Image = input.parse().unwrap();
+    let uri = http::Uri::try_from(&image).unwrap();
+    assert_eq!(uri.to_string(), expected);
+    assert_eq!(uri.scheme_str(), Some("https"));
+}
+
+#[test]
+fn missing_registry() {
+    let image: Image = "library/ubuntu".parse().unwrap();
+    assert_eq!(http::Uri::try_from(&image), Err(Error::MissingRegistry));
+}
@@ -0,0 +1,37 @@
+#![no_std]
+
+extern crate std;
+
+use core::error::Error;
+
+use oci_imgref::image::Image;
+use oci_imgref::{registry, repository};
+
+fn parse(input: &str) -> Result<Image, oci_imgref::image::Error> {
+    input.parse()
+}
+
+#[test]
+fn trait_objects() {
+    let errors: [&dyn Error; 3] = [
+        &registry::Error::Port,
+        &repository::Error::Container,
+        &oci_imgref::image::Error::Tag,
+    ];
+
+    for error in errors {
+        assert!(error.source().is_none());
+    }
+}
+
+#[test]
+fn sources() {
+    let error = parse("quay.io:0/foo/bar").unwrap_err();
+    let error: &dyn Error = &error;
+
+    let source = error.source().unwrap();
+    assert!(source.is::<repository::Error>());
+
+    let source = source.source().unwrap();
+    assert_eq!(source.downcast_ref(), Some(&registry::Error::Port));
+}
@@ -0,0 +1,265 @@
+use oci_imgref::digest::Error as DigestError;
+use oci_imgref::image::{Error, Image};
+use oci_imgref::options::ParseOptions;
+use oci_imgref::registry::Error as RegError;
+use oci_imgref::repository::Error as RepoError;
+
+const DIGEST: &str = "sha256:e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855";
+
+#[rstest::rstest]
+#[case("foo:latest", false, true)]
+#[case("foo@DIGEST", false, true)]
+#[case("foo:latest@DIGEST", false, true)]
+#[case("foo:latest", true, true)]
+#[case("foo@DIGEST", true, true)]
+#[case("foo:latest@DIGEST", true, false)]
+fn forbid_tag_and_digest(#[case] input: &str, #[case] forbid: bool, #[case] ok: bool) {
+    let input = input.replace("DIGEST", DIGEST);
+    let options = ParseOptions {
+        forbid_tag_and_digest: forbid,
+        ..Default::default()
+    };
+
+    match ok {
+        true => assert_eq!(Image::parse_with(&input, options), input.parse()),
+        false => assert_eq!(Image::parse_with(&input, options), Err(Error::TagAndDigest)),
+    }
+}
+
+#[rstest::rstest]
+#[case("foo:a.", false)]
+#[case("foo:a-", false)]
+#[case("foo:v1.0-@DIGEST", false)]
+#[case("foo:a.b", true)]
+#[case("foo:a_", true)]
+#[case("foo@DIGEST", true)]
+fn forbid_trailing_separator(#[case] input: &str, #[case] ok: bool) {
+    let input = input.replace("DIGEST", DIGEST);
+    let options = ParseOptions {
+        forbid_trailing_separator: true,
+        ..Default::default()
+    };
+
+    match ok {
+        true => assert_eq!(Image::parse_with(&input, options), input.parse()),
+        false => assert_eq!(Image::parse_with(&input, options), Err(Error::TagSeparator)),
+    }
+
+    assert!(Image::parse_with(&input, ParseOptions::default()).is_ok());
+    assert!(Image::parse_with(&input, ParseOptions::strict_oci()).is_ok());
+}
+
+#[rstest::rstest]
+#[case("ubuntu", Err(Error::MissingRegistry))]
+#[case("library/ubuntu:latest", Err(Error::MissingRegistry))]
+#[case("docker.io/ubuntu", Ok("docker.io/ubuntu"))]
+#[case("localhost:5000/ubuntu", Ok("localhost:5000/ubuntu"))]
+fn require_registry(#[case] input: &str, #[case] result: Result<&str, Error>) {
+    let options = ParseOptions {
+        require_registry: true,
+        ..Default::default()
+    };
+
+    let result = result.map(|s| s.parse().unwrap());
+    assert_eq!(Image::parse_with(input, options), result);
+    assert!(Image::parse_with(input, ParseOptions::default()).is_ok());
+}
+
+#[rstest::rstest]
+#[case("quay.io/library/foo", false)]
+#[case("localhost:5000/library/foo:v1", false)]
+#[case("docker.io/library/foo", true)]
+#[case("index.docker.io:443/library/foo", true)]
+#[case("library/foo", true)]
+#[case("quay.io/library/sub/foo", true)]
+#[case("quay.io/foo/library", true)]
+fn forbid_foreign_library(#[case] input: &str, #[case] ok: bool) {
+    let options = ParseOptions {
+        forbid_foreign_library: true,
+        ..Default::default()
+    };
+
+    match ok {
+        true => assert_eq!(Image::parse_with(input, options), input.parse()),
+        false => assert_eq!(
+            Image::parse_with(input, options),
+            Err(Error::ForeignLibrary)
+        ),
+    }
+
+    assert!(Image::parse_with(input, ParseOptions::default()).is_ok());
+}
+
+#[rstest::rstest]
+#[case("ubuntu", false, None)]
+#[case("ubuntu", true, Some("latest"))]
+#[case("ubuntu:22.04", true, Some("22.04"))]
+#[case("ubuntu@DIGEST", true, None)]
+#[case("ubuntu:22.04@DIGEST", true, Some("22.04"))]
+fn default_tag_latest(#[case] input: &str, #[case] latest: bool, #[case] tag: Option<&str>) {
+    let input = input.replace("DIGEST", DIGEST);
+    let options = ParseOptions {
+        default_tag_latest: latest,
+        ..Default::default()
+    };
+
+    let image = Image::parse_with(&input, options).unwrap();
+    assert_eq!(image.tag.as_deref(), tag);
+}
+
+#[rstest::rstest]
+#[case("Quay.IO/Foo/Bar", "quay.io/Foo/Bar")]
+#[case("LOCALHOST:5000/foo", "localhost:5000/foo")]
+#[case("[FE80::1%ETH0]/foo", "[fe80::1%ETH0]/foo")]
+#[case("[FE80::1%Eth0]:5000/foo", "[fe80::1%Eth0]:5000/foo")]
+#[case("Foo/Bar:Latest", "Foo/Bar:Latest")]
+fn lowercase_host(#[case] input: &str, #[case] expected: &str) {
+    let options = ParseOptions {
+        lowercase_host: true,
+        ..Default::default()
+    };
+
+    let image = Image::parse_with(input, options).unwrap();
+    assert_eq!(image.to_string(), expected);
+    assert_eq!(image, expected.parse().unwrap());
+    assert_eq!(
+        Image::parse_with(input, ParseOptions::default())
+            .unwrap()
+            .to_string(),
+        input
+    );
+}
+
+#[rstest::rstest]
+#[case("foo@DIGEST")]
+#[case("foo@UPPER")]
+#[case("Foo:Latest@UPPER")]
+#[case("foo@ALGORITHM")]
+fn lowercase_digest(#[case] input: &str) {
+    let upper = DIGEST.replace("e3b0", "E3B0");
+    let input = input.replace("UPPER", &upper);
+    let input = input.replace("ALGORITHM", &DIGEST.replace("sha", "SHA"));
+    let input = input.replace("DIGEST", DIGEST);
+    let expected = input.replace(&upper, DIGEST).replace("SHA", "sha");
+
+    let options = ParseOptions {
+        lowercase_digest: true,
+        ..Default::default()
+    };
+
+    let image = Image::parse_with(&input, options).unwrap();
+    assert_eq!(image.to_string(), expected);
+    assert_eq!(image, expected.parse().unwrap());
+
+    // Without the option, and under strict parsing, uppercase is rejected.
+    let strict = ParseOptions {
+        lowercase_digest: true,
+        ..ParseOptions::strict_oci()
+    };
+    assert_eq!(input.parse::<Image>().is_ok(), input == expected);
+    assert_eq!(Image::parse_with(&input, strict).is_ok(), input == expected);
+}
+
+#[rstest::rstest]
+#[case("ubuntu", "docker.io/library/ubuntu:latest")]
+#[case("ubuntu:22.04", "docker.io/library/ubuntu:22.04")]
+#[case("foo/bar", "docker.io/foo/bar:latest")]
+#[case("Docker.IO/ubuntu", "docker.io/library/ubuntu:latest")]
+#[case("index.docker.io/foo/bar", "docker.io/foo/bar:latest")]
+#[case("Quay.IO/foo/bar", "quay.io/foo/bar:latest")]
+#[case("localhost:5000/bar", "localhost:5000/bar:latest")]
+#[case("ubuntu@DIGEST", "docker.io/library/ubuntu@DIGEST")]
+fn docker_compat(#[case] input: &str, #[case] expected: &str) {
+    let input = input.replace("DIGEST", DIGEST);
+    let expected = expected.replace("DIGEST", DIGEST);
+
+    let image = Image::parse_with(&input, ParseOptions::docker_compat()).unwrap();
+    assert_eq!(image.to_string(), expected);
+}
+
+#[rstest::rstest]
+#[case("foo/bar", Ok(()))]
+#[case("quay.io/foo-bar/baz__qux:v1.0", Ok(()))]
+#[case("Foo/Bar", Err(Error::Repository(RepoError::Organization)))]
+#[case("foo/Bar", Err(Error::Repository(RepoError::Container)))]
+#[case("foo/bar.-baz", Err(Error::Repository(RepoError::Container)))]
+#[case("foo/bar:TAG", Err(Error::Tag))]
+#[case("foo/bar:v1.0-", Ok(()))]
+#[case("foo/bar:a.", Ok(()))]
+#[case("foo@DIGEST", Ok(()))]
+#[case("foo@UPPER", Err(Error::Digest(DigestError::Character)))]
+fn strict_oci(#[case] input: &str, #[case] result: Result<(), Error>) {
+    let input = input.replace("TAG", &"t".repeat(129));
+    let input = input.replace("DIGEST", DIGEST);
+    let input = input.replace("UPPER", &DIGEST.replace("e3b0", "E3B0"));
+
+    let image = Image::parse_with(&input, ParseOptions::strict_oci());
+    assert_eq!(image.map(|_| ()), result);
+}
+
+#[rstest::rstest]
+#[case("myhost/foo", Ok("myhost/foo"), Some("myhost"), None)]
+#[case("myhost:5000/foo:v1", Ok("myhost:5000/foo:v1"), Some("myhost"), None)]
+#[case("myhost/org/foo", Ok("myhost/org/foo"), Some("myhost"), Some("org"))]
+#[case("quay.io/foo", Ok("quay.io/foo"), Some("quay.io"), None)]
+#[case("foo", Ok("foo"), None, None)]
+#[case(
+    "my_host/foo",
+    Err(Error::Repository(RepoError::Registry(RegError::InvalidChar))),
+    None,
+    None
+)]
+fn single_label_registry(
+    #[case] input: &str,
+    #[case] expected: Result<&str, Error>,
+    #[case] host: Option<&str>,
+    #[case] organization: Option<&str>,
+) {
+    let options = ParseOptions {
+        single_label_registry: true,
+        ..Default::default()
+    };
+
+    let image = Image::parse_with(input, options);
+    assert_eq!(image.clone().map(String::from), expected.map(String::from));
+
+    if let Ok(image) = image {
+        let registry = image.repository.registry.as_ref();
+        assert_eq!(registry.map(|r| &*r.host), host);
+        assert_eq!(image.repository.organization.as_deref(), organization);
+
+        let reparsed = image.to_string().parse::<Image>();
+        assert_eq!(image.is_consistent(), reparsed == Ok(image));
+    }
+}
+
+#[rstest::rstest]
+#[case("docker.io//library//ubuntu", "docker.io/library/ubuntu")]
+#[case("quay.io///foo/bar:v1", "quay.io/foo/bar:v1")]
+#[case("foo//bar@DIGEST", "foo/bar@DIGEST")]
+#[case("foo/bar", "foo/bar")]
+fn collapse_slashes(#[case] input: &str, #[case] expected: &str) {
+    let input = input.replace("DIGEST", DIGEST);
+    let expected = expected.replace("DIGEST", DIGEST);
+
+    let options = ParseOptions {
+        collapse_slashes: true,
+        ..Default::default()
+    };
+
+    let image = Image::parse_with(&input, options).unwrap();
+    assert_eq!(image.to_string(), expected);
+
+    let strict = ParseOptions {
+        strict: true,
+        ..options
+    };
+
+    match input == expected {
+        true => assert_eq!(Image::parse_with(&input, strict), Ok(image)),
+        false => {
+            assert!(input.parse::<Image>().is_err());
+            assert!(Image::parse_with(&input, strict).is_err());
+        }
+    }
+}
@@ -1,5 +1,7 @@
+use core::cmp::Ordering;
+
 use oci_imgref::digest::Error as DigestError;
-use oci_imgref::image::{Error, Image};
+use oci_imgref::image::{CharClass, Error, Image};
 use oci_imgref::registry::Error as RegError;
 use oci_imgref::repository::Error as RepoError;
 
@@ -26,10 +28,25 @@ use oci_imgref::repository::Error as RepoError;
 #[case("quay.io/foo/bar:x", "quay.io", None, "foo", "bar", "x", None)]
 #[case("quay.io/foo/bar", "quay.io", None, "foo", "bar", None, None)]
 #[case("quay.io/foo", "quay.io", None, None, "foo", None, None)]
+#[case("gcr.io/foo/baz/bar:x", "gcr.io", None, "foo/baz", "bar", "x", None)]
 #[case("localhost/foo", "localhost", None, None, "foo", None, None)]
+#[case(
+    "[fe80::1%eth0]:5000/foo/bar:latest",
+    "[fe80::1%eth0]",
+    5000,
+    "foo",
+    "bar",
+    "latest",
+    None
+)]
 #[case("foo/bar", None, None, "foo", "bar", None, None)]
 #[case("foo", None, None, None, "foo", None, None)]
 #[case("foo:latest", None, None, None, "foo", "latest", None)]
+#[case("foo:a.b", None, None, None, "foo", "a.b", None)]
+#[case("foo:a_", None, None, None, "foo", "a_", None)]
+#[case("foo:a.", None, None, None, "foo", "a.", None)]
+#[case("foo:a..", None, None, None, "foo", "a..", None)]
+#[case("foo:v1.0-", None, None, None, "foo", "v1.0-", None)]
 #[case(
     "foo:latest@sha256:e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855",
     None,
@@ -58,6 +75,7 @@ fn image(
     #[case] digest: impl Into<Option<&'static str>>,
 ) {
     let image: Image = input.parse().unwrap();
+    assert_eq!(image.len(), image.to_string().len());
 
     match host.into() {
         None => assert!(image.repository.registry.is_none()),
@@ -67,7 +85,7 @@ fn image(
 
             match port.into() {
                 None => assert!(reg.port.is_none()),
-                Some(port) => assert_eq!(port, reg.port.unwrap().into()),
+                Some(port) => assert_eq!(port, reg.port.unwrap().get()),
             }
         }
     }
@@ -102,22 +120,1234 @@ fn image(
 #[case("foo@sha256:e3", Error::Digest(DigestError::Length))]
 #[case("foo@", Error::Digest(DigestError::Length))]
 #[case("foo:-", Error::Tag)]
+#[case("  ubuntu", Error::Whitespace)]
+#[case("ubuntu ", Error::Whitespace)]
+#[case("ubuntu:latest\n", Error::Whitespace)]
+#[case("library/ ubuntu", Error::Whitespace)]
+#[case("ubuntu\u{1b}[0m", Error::IllegalCharacter { found: CharClass::Control })]
+#[case("foo:v1\0", Error::IllegalCharacter { found: CharClass::Control })]
+#[case("quay.io/caf\u{e9}", Error::IllegalCharacter { found: CharClass::NonAscii })]
+#[case("\u{430}lpine", Error::IllegalCharacter { found: CharClass::NonAscii })]
+#[case("ubuntu\u{a0}\0", Error::Whitespace)]
 #[case("foo-", Error::Repository(RepoError::Container))]
 #[case("foo-/bar", Error::Repository(RepoError::Organization))]
 #[case("quay.io/foo-/bar", Error::Repository(RepoError::Organization))]
-#[case("quay.io-/foo", Error::Repository(RepoError::Registry(RegError::Host)))]
+#[case("quay.io/foo//bar", Error::Repository(RepoError::Organization))]
+#[case("quay.io/foo/-baz/bar", Error::Repository(RepoError::Organization))]
+#[case(
+    "quay.io-/foo",
+    Error::Repository(RepoError::Registry(RegError::TrailingDash))
+)]
 #[case(
     "quay.io-/foo/bar",
-    Error::Repository(RepoError::Registry(RegError::Host))
+    Error::Repository(RepoError::Registry(RegError::TrailingDash))
 )]
 #[case(
     "quay.io:/foo/bar",
-    Error::Repository(RepoError::Registry(RegError::Port))
+    Error::Repository(RepoError::Registry(RegError::PortNotNumeric))
 )]
+#[case(
+    ":1234/foo/bar",
+    Error::Repository(RepoError::Registry(RegError::Host))
+)]
+#[case(":/foo/bar", Error::Repository(RepoError::Registry(RegError::Host)))]
+#[case(
+    "%-tag:5000/:5000",
+    Error::Repository(RepoError::Registry(RegError::InvalidChar))
+)]
+#[case("quay.io/foo-/bar-", Error::Repository(RepoError::Organization))]
 #[case(
     "quay.io:abcd/foo/bar",
-    Error::Repository(RepoError::Registry(RegError::Port))
+    Error::Repository(RepoError::Registry(RegError::PortNotNumeric))
+)]
+#[case(
+    "quay.io:99999/foo/bar",
+    Error::Repository(RepoError::Registry(RegError::PortOverflow))
 )]
 fn failure(#[case] input: &'static str, #[case] error: Error) {
     assert_eq!(input.parse::<Image>().unwrap_err(), error);
 }
+
+#[test]
+fn try_from_str() {
+    use oci_imgref::registry::Registry;
+    use oci_imgref::repository::Repository;
+
+    fn convert<T: for<'a> TryFrom<&'a str>>(input: &str) -> Option<T> {
+        T::try_from(input).ok()
+    }
+
+    let input = "quay.io/foo/bar:latest";
+    let image = convert::<Image>(input).unwrap();
+    assert_eq!(image, input.parse().unwrap());
+    assert_eq!(
+        convert::<Repository>("quay.io/foo/bar").unwrap(),
+        image.repository
+    );
+    assert_eq!(
+        convert::<Registry>("quay.io"),
+        image.repository.registry.clone()
+    );
+
+    assert_eq!(Image::try_from("foo:-"), Err(Error::Tag));
+}
+
+#[test]
+fn try_from_bytes() {
+    use oci_imgref::registry::Registry;
+    use oci_imgref::repository::Repository;
+
+    let image = Image::try_from(&b"quay.io/foo/bar:latest"[..]).unwrap();
+    assert_eq!(image, "quay.io/foo/bar:latest".parse().unwrap());
+
+    let invalid = &b"quay.io/foo/\xffbar"[..];
+    assert_eq!(Image::try_from(invalid), Err(Error::Utf8));
+    assert_eq!(Repository::try_from(invalid), Err(RepoError::Utf8));
+    assert_eq!(Registry::try_from(&b"quay\xff.io"[..]), Err(RegError::Utf8));
+}
+
+#[test]
+fn digest_case() {
+    let mixed = "foo@sha256:E3b0C44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855";
+    assert_eq!(
+        mixed.parse::<Image>(),
+        Err(Error::Digest(DigestError::Character))
+    );
+
+    let upper = "foo@SHA256:E3B0C44298FC1C149AFBF4C8996FB92427AE41E4649B934CA495991B7852B855";
+    assert!(upper.parse::<Image>().is_err());
+    assert!(oci_imgref::image::parse_ranges(upper).is_err());
+
+    // The algorithm is not normalized either, unless asked to.
+    let algorithm = "foo@SHA256:e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855";
+    assert!(algorithm.parse::<Image>().is_err());
+
+    let options = oci_imgref::options::ParseOptions {
+        lowercase_digest: true,
+        ..Default::default()
+    };
+    let image = Image::parse_with(algorithm, options).unwrap();
+    assert_eq!(image.to_string(), algorithm.to_ascii_lowercase());
+}
+
+#[rstest::rstest]
+#[case("foo:latest", true)]
+#[case("quay.io/foo/bar:1.0", true)]
+#[case(
+    "foo:latest@sha256:e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855",
+    false
+)]
+#[case(
+    "foo@sha256:e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855",
+    false
+)]
+#[case("foo", false)]
+fn mutable_tag(#[case] input: &str, #[case] mutable: bool) {
+    let image: Image = input.parse().unwrap();
+    assert_eq!(image.is_mutable_tag(), mutable);
+}
+
+#[rstest::rstest]
+#[case("quay.io/foo/bar:main", true)]
+#[case("quay.io/foo/bar:nightly", true)]
+#[case("quay.io/foo/bar:v1.2.3", false)]
+#[case("quay.io/foo/bar:latest", false)]
+#[case("quay.io/foo/bar:Main", false)]
+#[case(
+    "foo:main@sha256:e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855",
+    false
+)]
+#[case("foo", false)]
+fn mutable_tag_in(#[case] input: &str, #[case] mutable: bool) {
+    let image: Image = input.parse().unwrap();
+    assert_eq!(image.is_mutable_tag_in(&["main", "nightly"]), mutable);
+}
+
+#[rstest::rstest]
+#[case("foo:sha-abc1234", true)]
+#[case("foo:sha-0123456789abcdef0123456789abcdef01234567", true)]
+#[case("foo:sha-abc123", false)]
+#[case("foo:sha-0123456789abcdef0123456789abcdef012345678", false)]
+#[case("foo:sha-ABC1234", false)]
+#[case("foo:sha-abc123g", false)]
+#[case("foo:20240115", true)]
+#[case("foo:20240115093000", true)]
+#[case("foo:2024", false)]
+#[case("foo:2024-01-15", false)]
+#[case("foo:latest", false)]
+#[case("foo", false)]
+fn tag_looks_immutable(#[case] input: &str, #[case] immutable: bool) {
+    let image: Image = input.parse().unwrap();
+    assert_eq!(image.tag_looks_immutable(), immutable);
+}
+
+#[rstest::rstest]
+#[case("ubuntu", "ubuntu")]
+#[case("localhost:5000/foo:v1", "localhost:5000/foo")]
+#[case(
+    "quay.io/foo/bar:v1@sha256:e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855",
+    "quay.io/foo/bar"
+)]
+fn family(#[case] input: &str, #[case] expected: &str) {
+    let image: Image = input.parse().unwrap();
+    assert_eq!(image.family(), expected);
+    assert_eq!(image.family(), image.repository.to_string());
+}
+
+#[rstest::rstest]
+#[case("foo/bar:v1", "v1")]
+#[case(
+    "foo/bar:v1@sha256:e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855",
+    "v1"
+)]
+#[case("foo/bar", "stable")]
+#[case(
+    "foo/bar@sha256:e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855",
+    "stable"
+)]
+fn tag_or(#[case] input: &str, #[case] tag: &str) {
+    let image: Image = input.parse().unwrap();
+    assert_eq!(image.tag_or("stable"), tag);
+}
+
+#[test]
+fn pin() {
+    let digest = "sha256:e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855";
+    let image: Image = "quay.io/foo/bar:latest".parse().unwrap();
+    let pinned = image.clone().pin(digest.parse().unwrap());
+
+    assert_eq!(pinned.repository, image.repository);
+    assert_eq!(pinned.tag.as_deref(), Some("latest"));
+    assert_eq!(pinned.digest.unwrap().to_string(), digest);
+}
+
+#[test]
+fn min_algorithm() {
+    use oci_imgref::image::Algorithm;
+
+    let image: Image =
+        "foo@sha256:e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+            .parse()
+            .unwrap();
+
+    assert_eq!(image.require_min_algorithm(Algorithm::Sha256), Ok(()));
+    assert_eq!(
+        image.require_min_algorithm(Algorithm::Sha384),
+        Err(Error::WeakAlgorithm)
+    );
+
+    let image: Image = "foo:latest".parse().unwrap();
+    assert_eq!(image.require_min_algorithm(Algorithm::Sha512), Ok(()));
+}
+
+#[rstest::rstest]
+#[case("quay.io:443/foo/baz/bar:latest@sha256:e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855")]
+#[case("[fe80::1%eth0]:5000/foo:latest")]
+#[case("quay.io/foo")]
+#[case("foo/bar@sha256:e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855")]
+#[case("foo:1.0")]
+#[case("foo")]
+fn ranges(#[case] input: &str) {
+    let image: Image = input.parse().unwrap();
+    let ranges = oci_imgref::image::parse_ranges(input).unwrap();
+
+    let registry = ranges.registry().map(|r| &input[r]);
+    let expected = image.repository.registry.as_ref().map(|r| r.to_string());
+    assert_eq!(registry, expected.as_deref());
+
+    assert_eq!(&input[ranges.path()], image.repository.to_registry_path());
+    match ranges.registry() {
+        Some(registry) => assert_eq!(registry.end + 1, ranges.path().start),
+        None => assert_eq!(ranges.path().start, 0),
+    }
+
+    let organization = ranges.organization().map(|r| &input[r]);
+    assert_eq!(organization, image.repository.organization.as_deref());
+
+    assert_eq!(&input[ranges.container()], image.repository.container);
+    assert_eq!(ranges.tag().map(|r| &input[r]), image.tag.as_deref());
+
+    let digest = image.digest.as_ref().map(|d| d.to_string());
+    assert_eq!(ranges.digest().map(|r| &input[r]), digest.as_deref());
+}
+
+#[rstest::rstest]
+#[case("sha256:e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855")]
+#[case("sha256:E3B0C44298FC1C149AFBF4C8996FB92427AE41E4649B934CA495991B7852B855")]
+#[case("SHA256:e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855")]
+#[case("sha384:38b060a751ac96384cd9327eb1b1e36a21fdb71114be07434c0cc7bf63f6e1da274edebfe76f65fbd51ad2f14898b95b")]
+#[case("sha512:cf83e1357eefb8bdf1542850d66d8007d620e4050b5715dc83f4a921d36ce9ce47d0d13c5d85f2b0ff8318d2877eec2f63b931bd47417a81a538327af927da3e")]
+#[case("sha512:e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855")]
+#[case("md5:d41d8cd98f00b204e9800998ecf8427e")]
+#[case("sha256:e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b85")]
+#[case("sha256:e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b8555")]
+#[case("sha256:e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b85g")]
+#[case("sha256:")]
+#[case("sha256")]
+fn ranges_digest(#[case] digest: &str) {
+    use oci_imgref::digest::Digest;
+
+    // `parse_ranges` checks digests itself, so it must agree with `Digest`.
+    let expected = digest.parse::<Digest>().map(|_| ()).map_err(Error::Digest);
+    let input = format!("foo@{digest}");
+    assert_eq!(
+        oci_imgref::image::parse_ranges(&input).map(|_| ()),
+        expected
+    );
+}
+
+#[rstest::rstest]
+#[case("foo:é")]
+#[case("foo:v1.é")]
+#[case("foo:ü1")]
+#[case("foo:v1\u{200b}")]
+fn multibyte_tag(#[case] input: &str) {
+    let found = CharClass::NonAscii;
+    assert_eq!(
+        input.parse::<Image>(),
+        Err(Error::IllegalCharacter { found })
+    );
+
+    let mut image: Image = "foo".parse().unwrap();
+    assert_eq!(image.set_tag(&input[4..]), Err(Error::Tag));
+
+    // 128 characters, but 129 bytes
+    let tag = format!("{}é", "a".repeat(oci_imgref::MAX_TAG_LEN - 1));
+    assert_eq!(image.set_tag(&tag), Err(Error::Tag));
+}
+
+#[test]
+fn limits() {
+    use oci_imgref::registry::Registry;
+    use oci_imgref::{MAX_HOST_LEN, MAX_LABEL_LEN, MAX_REFERENCE_LEN, MAX_TAG_LEN};
+
+    let tag = "a".repeat(MAX_TAG_LEN);
+    assert!(format!("foo:{tag}").parse::<Image>().is_ok());
+    assert_eq!(format!("foo:{tag}a").parse::<Image>(), Err(Error::Tag));
+
+    let label = "a".repeat(MAX_LABEL_LEN);
+    assert!(format!("{label}.io/foo").parse::<Image>().is_ok());
+    assert_eq!(
+        format!("{label}a.io/foo").parse::<Image>(),
+        Err(Error::Repository(RepoError::Registry(
+            RegError::LabelTooLong
+        )))
+    );
+
+    let host = [label.as_str(), &label, &label, &label[2..]].join(".");
+    assert_eq!(host.len(), MAX_HOST_LEN);
+    assert!(host.parse::<Registry>().is_ok());
+    assert_eq!(
+        format!("a{host}").parse::<Registry>(),
+        Err(RegError::HostTooLong)
+    );
+
+    let name = "quay.io/foo/".to_string() + &"a".repeat(MAX_REFERENCE_LEN - 12);
+    assert_eq!(name.len(), MAX_REFERENCE_LEN);
+    assert!(format!("{name}:{tag}").parse::<Image>().is_ok());
+    assert_eq!(
+        format!("{name}a:latest").parse::<Image>(),
+        Err(Error::Repository(RepoError::TooLong))
+    );
+}
+
+#[rstest::rstest]
+#[case("quay.io/foo/bar:1.0", "https://quay.io:443/v2/foo/bar/manifests/1.0")]
+#[case("localhost:5000/bar", "https://localhost:5000/v2/bar/manifests/latest")]
+#[case(
+    "quay.io/foo/bar:1.0@sha256:e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855",
+    "https://quay.io:443/v2/foo/bar/manifests/sha256:e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+)]
+fn manifest_url(#[case] input: &str, #[case] url: &str) {
+    use oci_imgref::registry::Scheme;
+
+    let image: Image = input.parse().unwrap();
+    assert_eq!(image.manifest_url(Scheme::Https).unwrap(), url);
+}
+
+#[test]
+fn manifest_url_without_registry() {
+    use oci_imgref::registry::Scheme;
+
+    let image: Image = "foo/bar:1.0".parse().unwrap();
+    assert_eq!(
+        image.manifest_url(Scheme::Https),
+        Err(Error::MissingRegistry)
+    );
+}
+
+#[test]
+fn reference() {
+    use oci_imgref::digest::Digest;
+    use oci_imgref::image::Reference;
+
+    let digest: Digest = "sha256:e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        .parse()
+        .unwrap();
+
+    let none = Reference::None;
+    assert_eq!(none.as_tag(), None);
+    assert_eq!(none.as_digest(), None);
+
+    let tag = Reference::Tag("latest".into());
+    assert_eq!(tag.as_tag(), Some("latest"));
+    assert_eq!(tag.as_digest(), None);
+
+    let dig = Reference::Digest(digest.clone());
+    assert_eq!(dig.as_tag(), None);
+    assert_eq!(dig.as_digest(), Some(&digest));
+
+    let both = Reference::Both("latest".into(), digest.clone());
+    assert_eq!(both.as_tag(), Some("latest"));
+    assert_eq!(both.as_digest(), Some(&digest));
+}
+
+#[rstest::rstest]
+#[case("ubuntu", Err(Error::Underspecified))]
+#[case("quay.io/foo/bar", Err(Error::Underspecified))]
+#[case("ubuntu:latest", Ok(()))]
+#[case(
+    "ubuntu@sha256:e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855",
+    Ok(())
+)]
+fn tag_or_digest(#[case] input: &str, #[case] result: Result<(), Error>) {
+    let image: Image = input.parse().unwrap();
+    assert_eq!(image.ensure_tag_or_digest(), result);
+}
+
+#[rstest::rstest]
+#[case("ubuntu", false)]
+#[case("ubuntu:latest", false)]
+#[case("quay.io/foo/bar", false)]
+#[case("quay.io/foo/bar:v1", true)]
+#[case("localhost:5000/bar@DIGEST", true)]
+#[case("docker.io/ubuntu:22.04@DIGEST", true)]
+fn is_fully_qualified(#[case] input: &str, #[case] qualified: bool) {
+    let digest = "sha256:e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855";
+    let image: Image = input.replace("DIGEST", digest).parse().unwrap();
+    assert_eq!(image.is_fully_qualified(), qualified);
+}
+
+#[rstest::rstest]
+#[case("foo:2024-06-01", "foo:2024-12-31", Some(Ordering::Less))]
+#[case("foo:20241231", "foo:20240601", Some(Ordering::Greater))]
+#[case("foo:2024.06.01", "bar:20240601", Some(Ordering::Equal))]
+#[case("foo:2023_12_31", "foo:2024-01-01", Some(Ordering::Less))]
+#[case("foo:latest", "foo:2024-06-01", None)]
+#[case("foo:2024-06-01", "foo", None)]
+#[case("foo:2024-13-01", "foo:2024-06-01", None)]
+#[case("foo:2024-06-00", "foo:2024-06-01", None)]
+#[case("foo:2024-06.01", "foo:2024-06-01", None)]
+#[case("foo:v2024-06-01", "foo:2024-06-01", None)]
+#[case("foo:12345678901", "foo:2024-06-01", None)]
+fn cmp_tags_datelike(#[case] a: &str, #[case] b: &str, #[case] expected: Option<Ordering>) {
+    let a: Image = a.parse().unwrap();
+    let b: Image = b.parse().unwrap();
+    assert_eq!(a.cmp_tags_datelike(&b), expected);
+}
+
+#[rstest::rstest]
+#[case("ubuntu", "docker.io/library/ubuntu:latest", true)]
+#[case("ubuntu:latest", "library/ubuntu", true)]
+#[case("docker.io/ubuntu", "index.docker.io/library/ubuntu:latest", true)]
+#[case("registry-1.docker.io/library/ubuntu", "ubuntu", true)]
+#[case("registry.hub.docker.com/ubuntu", "ubuntu", true)]
+#[case("Quay.IO/foo/bar", "quay.io/foo/bar:latest", true)]
+#[case("quay.io/ubuntu", "ubuntu", false)]
+#[case("ubuntu:22.04", "ubuntu", false)]
+#[case("foo/ubuntu", "ubuntu", false)]
+#[case("docker.io:443/ubuntu", "docker.io/ubuntu", true)]
+#[case("index.docker.io:443/library/ubuntu:latest", "ubuntu", true)]
+#[case("docker.io:5000/ubuntu", "docker.io/ubuntu", false)]
+#[case("quay.io:443/foo/bar", "quay.io/foo/bar", false)]
+#[case("[FE80::1%eth0]/foo", "[fe80::1%eth0]/foo", true)]
+#[case("[fe80::1%Eth0]/foo", "[fe80::1%eth0]/foo", false)]
+fn canonical_eq(#[case] a: &str, #[case] b: &str, #[case] eq: bool) {
+    let a: Image = a.parse().unwrap();
+    let b: Image = b.parse().unwrap();
+    assert_eq!(a.canonical_eq(&b), eq);
+    assert_eq!(b.canonical_eq(&a), eq);
+}
+
+#[rstest::rstest]
+#[case("ubuntu", "docker.io/library/ubuntu:latest", true)]
+#[case("ubuntu:22.04", "ubuntu@DIGEST", true)]
+#[case("ubuntu:22.04@DIGEST", "ubuntu:24.04@DIGEST", true)]
+#[case("ubuntu@DIGEST", "docker.io/library/ubuntu@DIGEST", true)]
+#[case("ubuntu:1@DIGEST", "ubuntu:2", true)]
+#[case("ubuntu:2", "ubuntu:1@DIGEST", true)]
+#[case("ubuntu:22.04", "ubuntu:24.04", false)]
+#[case("ubuntu:22.04@DIGEST", "ubuntu:22.04@OTHER", false)]
+#[case("ubuntu@DIGEST", "debian@DIGEST", false)]
+fn may_be_same_content(#[case] a: &str, #[case] b: &str, #[case] same: bool) {
+    let digest = "sha256:e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855";
+    let other = "sha256:".to_string() + &"0".repeat(64);
+    let parse = |s: &str| -> Image {
+        let s = s.replace("DIGEST", digest).replace("OTHER", &other);
+        s.parse().unwrap()
+    };
+
+    let (a, b) = (parse(a), parse(b));
+    assert_eq!(a.may_be_same_content(&b), same);
+    assert_eq!(b.may_be_same_content(&a), same);
+}
+
+#[test]
+fn canonical_hash() {
+    use oci_imgref::image::Canonical;
+    use std::collections::HashSet;
+
+    let spellings = [
+        "ubuntu",
+        "ubuntu:latest",
+        "library/ubuntu",
+        "docker.io/ubuntu:latest",
+        "docker.io/library/ubuntu",
+        "index.docker.io/library/ubuntu:latest",
+    ];
+
+    let set: HashSet<_> = spellings
+        .iter()
+        .map(|s| Canonical(s.parse::<Image>().unwrap()))
+        .collect();
+    assert_eq!(set.len(), 1);
+
+    let other = Canonical("quay.io/ubuntu".parse::<Image>().unwrap());
+    assert!(!set.contains(&other));
+}
+
+#[rstest::rstest]
+#[case("quay.io:443/foo/bar", Some(("quay.io", Some(443))))]
+#[case("quay.io/foo/bar", Some(("quay.io", None)))]
+#[case("foo/bar", None)]
+fn registry_and_port(#[case] input: &str, #[case] expected: Option<(&str, Option<u16>)>) {
+    let image: Image = input.parse().unwrap();
+    let actual = image
+        .registry_and_port()
+        .map(|(host, port)| (host, port.map(|p| p.get())));
+    assert_eq!(actual, expected);
+}
+
+#[test]
+fn components() {
+    use oci_imgref::image::Component;
+
+    let image: Image = "quay.io:443/foo/bar:latest@sha256:e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        .parse()
+        .unwrap();
+
+    let components: Vec<_> = image.components().collect();
+    assert_eq!(
+        components,
+        [
+            Component::Registry(image.repository.registry.as_ref().unwrap()),
+            Component::Organization("foo"),
+            Component::Container("bar"),
+            Component::Tag("latest"),
+            Component::Digest(image.digest.as_ref().unwrap()),
+        ]
+    );
+
+    let image: Image = "bar".parse().unwrap();
+    let components: Vec<_> = image.components().collect();
+    assert_eq!(components, [Component::Container("bar")]);
+}
+
+#[rstest::rstest]
+#[case("ubuntu")]
+#[case("library/ubuntu:22.04")]
+#[case("localhost:5000/foo/bar/baz:v1")]
+#[case("[fe80::1%eth0]:65535/foo@sha256:e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855")]
+fn into_string(#[case] input: &str) {
+    let image: Image = input.parse().unwrap();
+    let expected = image.to_string();
+
+    let string = String::from(image);
+    assert_eq!(string, expected);
+    assert_eq!(string.capacity(), string.len());
+}
+
+#[test]
+fn setters() {
+    let digest = "sha256:e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855";
+    let mut image: Image = "quay.io/foo/bar:latest".parse().unwrap();
+    let before = image.clone();
+
+    assert_eq!(image.set_tag("-latest"), Err(Error::Tag));
+    assert_eq!(
+        image.set_digest("sha256:e3b0"),
+        Err(Error::Digest(DigestError::Length))
+    );
+    assert_eq!(image, before);
+
+    image.set_tag("v1").unwrap();
+    image.set_digest(digest).unwrap();
+    assert_eq!(image.to_string(), format!("quay.io/foo/bar:v1@{digest}"));
+}
+
+#[test]
+fn error_set() {
+    use std::collections::HashSet;
+
+    let inputs = [
+        "foo:-bar",
+        "foo:.bar",
+        "foo-/bar",
+        "quay.io:0/foo",
+        "quay.io:00/foo",
+        "foo@sha256:e3b0",
+        "foo@sha256:e3b1",
+    ];
+
+    let errors: HashSet<Error> = inputs
+        .iter()
+        .map(|s| s.parse::<Image>().unwrap_err())
+        .collect();
+
+    assert_eq!(errors.len(), 4);
+    assert!(errors.contains(&Error::Tag));
+    assert!(errors.contains(&Error::Repository(RepoError::Organization)));
+    assert!(errors.contains(&Error::Repository(RepoError::Registry(RegError::Port))));
+    assert!(errors.contains(&Error::Digest(DigestError::Length)));
+}
+
+#[rstest::rstest]
+#[case(
+    "foo:-bar",
+    "invalid tag (expected `[A-Za-z0-9_][A-Za-z0-9._-]*`, at most 128 characters)"
+)]
+#[case("foo-", "(expected `[A-Za-z0-9]([A-Za-z0-9._-]*[A-Za-z0-9])?`)")]
+#[case(
+    "foo-/bar",
+    "(expected `/`-separated `[A-Za-z0-9]([A-Za-z0-9._-]*[A-Za-z0-9])?`)"
+)]
+#[case("quay.io:0/foo", "invalid port: zero (expected 1-65535)")]
+#[case(
+    ":1234/foo/bar",
+    "invalid repository: invalid registry: invalid host: empty"
+)]
+#[case("quay_io:1/foo", "(expected `.`-separated labels of `[A-Za-z0-9-]`)")]
+#[case("[::g]:1/foo", "(expected `[addr]` or `[addr%zone]`)")]
+#[case("foo@sha256:e3b0", "(expected `algorithm:hex`)")]
+fn error_hints(#[case] input: &str, #[case] hint: &str) {
+    let error = input.parse::<Image>().unwrap_err().to_string();
+    assert!(error.contains(hint), "{error:?} lacks {hint:?}");
+}
+
+#[test]
+fn reference_mut() {
+    use oci_imgref::image::Reference;
+
+    let digest = "sha256:e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855";
+    let mut image: Image = "quay.io/foo/bar:latest".parse().unwrap();
+    let before = image.clone();
+
+    let mut reference = image.reference_mut();
+    reference
+        .tag(Some("-bad"))
+        .digest(Some(digest.parse().unwrap()));
+    assert_eq!(reference.commit(), Err(Error::Tag));
+    assert_eq!(image, before);
+
+    let mut reference = image.reference_mut();
+    reference.tag(None).digest(Some(digest.parse().unwrap()));
+    drop(reference);
+    assert_eq!(image, before);
+
+    let mut reference = image.reference_mut();
+    reference.tag(None).digest(Some(digest.parse().unwrap()));
+    reference.commit().unwrap();
+    assert_eq!(
+        image.reference(),
+        Reference::Digest(digest.parse().unwrap())
+    );
+    assert_eq!(image.to_string(), format!("quay.io/foo/bar@{digest}"));
+}
+
+#[test]
+fn normalize_images() {
+    use oci_imgref::image::normalize_images;
+
+    let inputs = [
+        "ubuntu",
+        "quay.io/foo/bar:v1",
+        "library/ubuntu:latest",
+        "Quay.IO/foo/bar:v1",
+        "index.docker.io/library/ubuntu",
+        "ubuntu:22.04",
+        "quay.io/foo/bar:v1",
+    ];
+
+    let images: Vec<Image> = inputs.iter().map(|s| s.parse().unwrap()).collect();
+    let images: Vec<String> = normalize_images(&images)
+        .into_iter()
+        .map(String::from)
+        .collect();
+
+    assert_eq!(
+        images,
+        [
+            "docker.io/library/ubuntu:latest",
+            "quay.io/foo/bar:v1",
+            "docker.io/library/ubuntu:22.04",
+        ]
+    );
+}
+
+#[test]
+fn write_to() {
+    let input = "quay.io/foo/bar:latest";
+    let image: Image = input.parse().unwrap();
+
+    let mut buffer = heapless::String::<22>::new();
+    image.write_to(&mut buffer).unwrap();
+    assert_eq!(buffer, input);
+
+    // The buffer is one byte short.
+    let mut buffer = heapless::String::<21>::new();
+    assert!(image.write_to(&mut buffer).is_err());
+}
+
+#[rstest::rstest]
+#[case("ubuntu")]
+#[case("library/ubuntu:22.04")]
+#[case("localhost:5000/foo/bar/baz:v1")]
+#[case("[fe80::1%eth0]:5000/foo@sha256:e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855")]
+#[case("quay.io/foo/bar:latest@sha256:e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855")]
+fn parse(#[case] input: &str) {
+    let parsed = oci_imgref::parse(input).unwrap();
+    let (image, ranges) = (parsed.image, parsed.ranges);
+    assert_eq!(image, input.parse().unwrap());
+    assert_eq!(ranges, oci_imgref::image::parse_ranges(input).unwrap());
+
+    let registry = image.repository.registry.as_ref().map(ToString::to_string);
+    assert_eq!(ranges.registry().map(|r| &input[r]), registry.as_deref());
+    assert_eq!(
+        ranges.organization().map(|r| &input[r]),
+        image.repository.organization.as_deref()
+    );
+    assert_eq!(&input[ranges.container()], image.repository.container);
+    assert_eq!(ranges.tag().map(|r| &input[r]), image.tag.as_deref());
+
+    let digest = image.digest.as_ref().map(ToString::to_string);
+    let span = ranges.digest().map(|r| input[r].to_ascii_lowercase());
+    assert_eq!(span, digest);
+}
+
+#[rstest::rstest]
+#[case("quay.io/foo:latest", Some("quay.io/foo:latest"), &[])]
+#[case("quay.io/foo:-bar", Some("quay.io/foo"), &[(Error::Tag, 12..16)])]
+#[case(
+    "quay-.io/foo:-bar",
+    None,
+    &[
+        (Error::Repository(RepoError::Registry(RegError::TrailingDash)), 0..8),
+        (Error::Tag, 13..17),
+    ]
+)]
+#[case(
+    "quay.io/-foo/bar-:.x@sha256:e3",
+    None,
+    &[
+        (Error::Repository(RepoError::Organization), 8..12),
+        (Error::Repository(RepoError::Container), 13..17),
+        (Error::Tag, 18..20),
+        (Error::Digest(DigestError::Length), 21..30),
+    ]
+)]
+#[case("foo/bar :v1", None, &[(Error::Whitespace, 7..8)])]
+#[case("foo/b\u{e4}r:v1", None, &[(Error::IllegalCharacter { found: CharClass::NonAscii }, 5..7)])]
+fn parse_recoverable(
+    #[case] input: &str,
+    #[case] image: Option<&str>,
+    #[case] expected: &[(Error, std::ops::Range<usize>)],
+) {
+    let (parsed, errors) = oci_imgref::image::parse_recoverable(input);
+    assert_eq!(parsed.map(String::from).as_deref(), image);
+
+    let errors: Vec<_> = errors.into_iter().map(|e| (e.error, e.range)).collect();
+    assert_eq!(errors, expected);
+}
+
+#[test]
+fn validate_strict() {
+    use oci_imgref::registry::Registry;
+    use oci_imgref::repository::Repository;
+
+    let strict = |input: &str| input.parse::<Image>().unwrap().validate_strict();
+    assert_eq!(strict("quay.io:5000/foo-bar/baz__qux:Latest"), Ok(()));
+    assert_eq!(
+        strict("Foo/bar"),
+        Err(Error::Repository(RepoError::Organization))
+    );
+    assert_eq!(
+        strict("foo/bar..baz"),
+        Err(Error::Repository(RepoError::Container))
+    );
+
+    let mut image = Image {
+        repository: Repository {
+            registry: Some(Registry::docker_hub()),
+            organization: Some("library".into()),
+            container: "Ubuntu".into(),
+        },
+        tag: Some("latest".into()),
+        digest: None,
+    };
+
+    assert_eq!(
+        image.validate_strict(),
+        Err(Error::Repository(RepoError::Container))
+    );
+
+    image.repository.container = "ubuntu".into();
+    assert_eq!(image.validate_strict(), Ok(()));
+
+    image.tag = Some(".latest".into());
+    assert_eq!(image.validate_strict(), Err(Error::Tag));
+
+    image.tag = None;
+    image.repository.registry.as_mut().unwrap().host = "docker..io".into();
+    assert_eq!(
+        image.validate_strict(),
+        Err(Error::Repository(RepoError::Registry(RegError::EmptyLabel)))
+    );
+
+    image.repository.registry = None;
+    image.repository.organization = Some("a".repeat(250).into());
+    assert_eq!(
+        image.validate_strict(),
+        Err(Error::Repository(RepoError::TooLong))
+    );
+
+    // Without a registry, this would parse back as one.
+    image.repository.organization = Some("example.com".into());
+    assert_eq!(
+        image.validate_strict(),
+        Err(Error::Repository(RepoError::Organization))
+    );
+    assert!(!image.is_consistent());
+}
+
+#[test]
+fn feature_independent_errors() {
+    // Exhaustive matches must not depend on which features are enabled.
+    assert_eq!(Error::Uri.to_string(), "invalid URI");
+    assert!(Error::Url.to_string().starts_with("invalid URL"));
+}
+
+#[test]
+fn builder() {
+    use oci_imgref::digest::Digest;
+    use oci_imgref::registry::Registry;
+
+    let digest = "sha256:e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855";
+    let typed: Digest = digest.parse().unwrap();
+
+    // Only typed components: `build` returns the image itself.
+    let image: Image = Image::builder("docker.io/foo/bar".parse().unwrap())
+        .digest(typed.clone())
+        .build();
+    assert_eq!(image.to_string(), format!("docker.io/foo/bar@{digest}"));
+
+    let image = Image::builder("foo/bar".parse().unwrap())
+        .registry(Registry::docker_hub())
+        .digest(typed.clone())
+        .build();
+    assert_eq!(
+        image.map(String::from),
+        Ok(format!("docker.io/foo/bar@{digest}"))
+    );
+
+    // A registry is checked together with the repository it joins.
+    let long = "a".repeat(oci_imgref::MAX_REFERENCE_LEN - 1);
+    let image = Image::builder(long.parse().unwrap()).registry(Registry::docker_hub());
+    assert_eq!(
+        image.clone().build(),
+        Err(Error::Repository(RepoError::TooLong))
+    );
+    assert_eq!(
+        image.registry_str("quay.io").build(),
+        Err(Error::Repository(RepoError::TooLong))
+    );
+
+    let image = Image::builder("app".parse().unwrap())
+        .registry_str("myhost")
+        .build();
+    assert_eq!(image, Err(Error::Repository(RepoError::AmbiguousRegistry)));
+
+    let image = Image::builder("docker.io/foo/bar".parse().unwrap())
+        .registry_str("quay.io:5000")
+        .tag("v1")
+        .digest_str(digest)
+        .build();
+    assert_eq!(
+        image.map(String::from),
+        Ok(format!("quay.io:5000/foo/bar:v1@{digest}"))
+    );
+
+    let image = Image::builder("foo/bar".parse().unwrap())
+        .registry_str("quay.io:0")
+        .tag("-v1")
+        .digest(typed)
+        .build();
+    assert_eq!(
+        image,
+        Err(Error::Repository(RepoError::Registry(RegError::Port)))
+    );
+}
+
+#[test]
+fn checked_display() {
+    use oci_imgref::registry::Registry;
+    use oci_imgref::repository::Repository;
+
+    let inputs = [
+        "ubuntu",
+        "library/ubuntu:latest",
+        "quay.io/foo/bar/baz:v1@sha256:e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855",
+        "[::1]:5000/foo",
+    ];
+
+    for input in inputs {
+        let image: Image = input.parse().unwrap();
+        assert_eq!(image.checked_display().as_deref(), Ok(input));
+    }
+
+    let valid = Image {
+        repository: Repository {
+            registry: None,
+            organization: Some("foo".into()),
+            container: "bar".into(),
+        },
+        tag: None,
+        digest: None,
+    };
+
+    let cases = [
+        (|i: &mut Image| i.repository.container = "bar/baz".into()) as fn(&mut Image),
+        |i| i.repository.container = "".into(),
+        |i| i.repository.organization = Some("foo/baz".into()),
+        |i| i.repository.organization = Some("localhost".into()),
+        |i| {
+            i.repository.registry = Some(Registry {
+                host: "quay io".into(),
+                port: None,
+            })
+        },
+        |i| i.tag = Some("v1@x".into()),
+    ];
+
+    for case in cases {
+        let mut image = valid.clone();
+        case(&mut image);
+        assert!(image.checked_display().is_err(), "{image}");
+    }
+}
+
+#[test]
+fn as_ref_repository() {
+    use oci_imgref::repository::Repository;
+
+    fn path(repo: impl AsRef<Repository>) -> String {
+        repo.as_ref().to_registry_path()
+    }
+
+    let image: Image = "quay.io/foo/bar:latest".parse().unwrap();
+    assert_eq!(path(&image), "foo/bar");
+    assert_eq!(path(&image.repository), "foo/bar");
+    assert_eq!(path(image.repository), "foo/bar");
+}
+
+#[test]
+fn lookup_by_repository() {
+    use oci_imgref::repository::Repository;
+    use std::collections::HashMap;
+
+    let images: Vec<Image> = ["quay.io/foo/bar:v1", "quay.io/foo/baz:v1"]
+        .iter()
+        .map(|s| s.parse().unwrap())
+        .collect();
+
+    let map: HashMap<Repository, usize> = images
+        .iter()
+        .enumerate()
+        .map(|(i, image)| (image.repository.clone(), i))
+        .collect();
+
+    let image: Image =
+        "quay.io/foo/baz@sha256:e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+            .parse()
+            .unwrap();
+    assert_eq!(map.get(image.as_ref()), Some(&1));
+    assert_eq!(map.get(&image.repository), Some(&1));
+}
+
+#[rstest::rstest]
+#[case("quay.io/*/bar:*", "quay.io/foo/bar:v1", Some(vec!["foo", "v1"]))]
+#[case("quay.io/*/*:*", "quay.io/foo/bar:v1", Some(vec!["foo", "bar", "v1"]))]
+#[case("quay.io/*:*", "quay.io/a/b/c:v1", Some(vec!["a/b/c", "v1"]))]
+#[case("*", "quay.io/foo/bar", Some(vec!["quay.io/foo/bar"]))]
+#[case("quay.io/foo/bar*", "quay.io/foo/bar", Some(vec![""]))]
+#[case("quay.io/foo/bar", "quay.io/foo/bar", Some(vec![]))]
+#[case("quay.io/*/bar:*", "quay.io/foo/baz:v1", None)]
+#[case("quay.io/*:v1", "docker.io/foo/bar:v1", None)]
+fn captures(#[case] pattern: &str, #[case] input: &str, #[case] expected: Option<Vec<&str>>) {
+    use oci_imgref::image::ImagePattern;
+
+    let image: Image = input.parse().unwrap();
+    let pattern = ImagePattern::from(pattern);
+
+    let captures = pattern.captures(&image);
+    let captured = captures.as_ref().map(|c| c.iter().collect::<Vec<_>>());
+    assert_eq!(captured, expected);
+    assert_eq!(image.matches(&pattern), expected.is_some());
+}
+
+#[rstest::rstest]
+#[case(
+    "docker.io/library/ubuntu:latest",
+    "mirror.internal:5000/library/ubuntu:latest"
+)]
+#[case("docker.io/foo/bar@DIGEST", "mirror.internal:5000/foo/bar@DIGEST")]
+#[case("quay.io/foo/bar:v1", "quay.io/foo/bar:v1")]
+#[case("ubuntu", "ubuntu")]
+fn rewrite_registry(#[case] input: &str, #[case] expected: &str) {
+    use oci_imgref::registry::{Registry, RegistryPattern};
+
+    let digest = "sha256:e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855";
+    let input = input.replace("DIGEST", digest);
+    let expected = expected.replace("DIGEST", digest);
+
+    let from: RegistryPattern = "docker.io".parse().unwrap();
+    let to: Registry = "mirror.internal:5000".parse().unwrap();
+
+    let image: Image = input.parse().unwrap();
+    assert_eq!(image.rewrite_registry(&from, &to).to_string(), expected);
+}
+
+#[test]
+fn rewrite_all() {
+    use oci_imgref::image::rewrite_all;
+
+    let rules = [
+        (
+            "docker.io".parse().unwrap(),
+            "mirror.corp.example".parse().unwrap(),
+        ),
+        (
+            "*.example".parse().unwrap(),
+            "cache.internal".parse().unwrap(),
+        ),
+    ];
+
+    let mut images: Vec<Image> = [
+        "docker.io/library/ubuntu:latest",
+        "quay.io/foo/bar:v1",
+        "registry.example/foo/bar",
+        "ubuntu",
+    ]
+    .iter()
+    .map(|s| s.parse().unwrap())
+    .collect();
+
+    rewrite_all(&mut images, &rules);
+
+    let images: Vec<String> = images.iter().map(Image::to_string).collect();
+    assert_eq!(
+        images,
+        [
+            "mirror.corp.example/library/ubuntu:latest",
+            "quay.io/foo/bar:v1",
+            "cache.internal/foo/bar",
+            "ubuntu",
+        ]
+    );
+}
+
+#[test]
+fn ord() {
+    let a = "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855";
+    let b = "f3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855";
+
+    let mut images: Vec<Image> = [
+        format!("quay.io/foo/bar:v1@sha256:{b}"),
+        format!("quay.io/foo/bar:v2@sha256:{a}"),
+        format!("quay.io/foo/bar:v1@sha256:{a}"),
+        "quay.io/foo/bar:v1".into(),
+        "foo/bar:v1".into(),
+    ]
+    .iter()
+    .map(|s| s.parse().unwrap())
+    .collect();
+
+    images.sort();
+
+    let images: Vec<String> = images.iter().map(Image::to_string).collect();
+    assert_eq!(
+        images,
+        [
+            "foo/bar:v1".into(),
+            "quay.io/foo/bar:v1".into(),
+            format!("quay.io/foo/bar:v1@sha256:{a}"),
+            format!("quay.io/foo/bar:v1@sha256:{b}"),
+            format!("quay.io/foo/bar:v2@sha256:{a}"),
+        ]
+    );
+}
+
+#[test]
+fn parse_list() {
+    use oci_imgref::image::parse_list;
+
+    let input = "# base images\n  ubuntu:22.04\n\nquay.io/foo/bar\r\n   \n# done\n";
+    let images: Vec<String> = parse_list(input)
+        .unwrap()
+        .iter()
+        .map(Image::to_string)
+        .collect();
+    assert_eq!(images, ["ubuntu:22.04", "quay.io/foo/bar"]);
+
+    let input = "ubuntu\n\n# comment\nfoo/bar:\nquay.io/foo/bar\n";
+    assert_eq!(parse_list(input), Err((3, Error::Tag)));
+
+    assert_eq!(parse_list(""), Ok(vec![]));
+}
+
+#[rstest::rstest]
+#[case("ubuntu", "docker.io/library/ubuntu:latest")]
+#[case("Quay.IO/foo/bar:v1", "quay.io/foo/bar:v1")]
+#[case("foo/bar@DIGEST", "docker.io/foo/bar@DIGEST")]
+#[case(
+    "index.docker.io/ubuntu:22.04@DIGEST",
+    "docker.io/library/ubuntu:22.04@DIGEST"
+)]
+fn to_pull_string(#[case] input: &str, #[case] expected: &str) {
+    let digest = "sha256:e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855";
+    let input = input.replace("DIGEST", digest);
+    let expected = expected.replace("DIGEST", digest);
+
+    let image: Image = input.parse().unwrap();
+    assert_eq!(image.to_pull_string(), expected);
+    assert_eq!(image.to_string(), input);
+}
+
+#[rstest::rstest]
+#[case("quay.io/foo/bar:latest")]
+#[case("foo@sha256:e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855")]
+#[case("localhost:5000/a/b/c")]
+fn into_repository(#[case] input: &str) {
+    let image: Image = input.parse().unwrap();
+    let repository = image.repository.clone();
+    assert_eq!(image.into_repository(), repository);
+}
+
+#[test]
+fn const_context() {
+    use core::num::NonZeroU16;
+    use oci_imgref::options::ParseOptions;
+    use oci_imgref::registry::{Registry, Scheme};
+
+    const HUB: Registry = Registry::docker_hub();
+    const MIRROR: Registry = Registry::from_static("mirror.example.com", NonZeroU16::new(5000));
+    const HTTPS: u16 = Scheme::Https.default_port();
+    const OPTIONS: ParseOptions = ParseOptions::docker_compat();
+
+    assert!(HUB.is_default());
+    assert_eq!(MIRROR.port_or(HTTPS), 5000);
+    assert_eq!(MIRROR.to_string(), "mirror.example.com:5000");
+
+    let image = Image::parse_with("ubuntu", OPTIONS).unwrap();
+    assert_eq!(image.repository.registry, Some(HUB));
+    assert!(image.is_mutable_tag());
+}
+
+#[test]
+fn static_and_owned() {
+    use oci_imgref::registry::Registry;
+    use oci_imgref::repository::Repository;
+    use std::borrow::Cow;
+    use std::hash::{BuildHasher, RandomState};
+
+    const INPUT: &str = "docker.io/library/ubuntu:latest";
+    const STATIC: Image = Image {
+        repository: Repository::from_static(
+            Some(Registry::docker_hub()),
+            Some("library"),
+            "ubuntu",
+        ),
+        tag: Some(Cow::Borrowed("latest")),
+        digest: None,
+    };
+
+    let owned: Image = INPUT.parse().unwrap();
+    assert!(matches!(owned.repository.container, Cow::Owned(_)));
+    assert!(matches!(STATIC.repository.container, Cow::Borrowed(_)));
+
+    let state = RandomState::new();
+    assert_eq!(STATIC, owned);
+    assert_eq!(state.hash_one(&STATIC), state.hash_one(&owned));
+    assert_eq!(STATIC.cmp(&owned), Ordering::Equal);
+
+    for image in [STATIC, owned] {
+        assert_eq!(image.to_string(), INPUT);
+        assert_eq!(image.to_string().parse::<Image>().unwrap(), image);
+    }
+}
+
+#[rstest::rstest]
+#[case("quay.io/foo/bar:latest", None, "'quay.io/foo/bar:latest'")]
+#[case("foo", Some("it's"), r"'foo:it'\''s'")]
+#[case("foo", Some("'; rm -rf ~ #"), r"'foo:'\''; rm -rf ~ #'")]
+fn shell_quoted(#[case] input: &str, #[case] tag: Option<&str>, #[case] expected: &str) {
+    let mut image: Image = input.parse().unwrap();
+    if let Some(tag) = tag {
+        image.tag = Some(tag.to_string().into());
+    }
+
+    let quoted = image.shell_quoted();
+    assert_eq!(quoted, expected);
+
+    // Undo the quoting as `sh` would: outside quotes, only `\'` may appear.
+    let mut unquoted = String::new();
+    let mut rest = quoted.as_str();
+    while let Some(word) = rest.strip_prefix('\'') {
+        let (inside, after) = word.split_once('\'').unwrap();
+        unquoted.push_str(inside);
+        rest = match after.strip_prefix(r"\'") {
+            Some(after) => {
+                unquoted.push('\'');
+                after
+            }
+            None => after,
+        };
+    }
+
+    assert_eq!(rest, "");
+    assert_eq!(unquoted, image.to_string());
+}
+
+#[test]
+fn is_consistent() {
+    let image: Image = "quay.io/foo/bar:latest".parse().unwrap();
+    assert!(image.is_consistent());
+    assert_eq!(image.repository(), &image.repository);
+
+    let corruptions: [fn(&mut Image); 6] = [
+        |i| i.tag = Some("".into()),
+        |i| i.tag = Some("a b".into()),
+        |i| i.repository.container = "Bar/baz".into(),
+        |i| i.repository.organization = Some("foo//bar".into()),
+        |i| i.repository.registry.as_mut().unwrap().host = "quay..io".into(),
+        |i| {
+            i.repository.registry = None;
+            i.repository.organization = Some("example.com".into());
+        },
+    ];
+
+    for corrupt in corruptions {
+        let mut image = image.clone();
+        corrupt(&mut image);
+        assert!(!image.is_consistent(), "{image:?}");
+    }
+}
@@ -0,0 +1,20 @@
+#![cfg(feature = "semver")]
+
+use core::cmp::Ordering;
+use oci_imgref::image::Image;
+
+#[rstest::rstest]
+#[case("foo:1.9.0", "foo:1.10.0", Some(Ordering::Less))]
+#[case("foo:1.10.0", "foo:1.9.0", Some(Ordering::Greater))]
+#[case("foo:1.0.0", "bar:1.0.0", Some(Ordering::Equal))]
+#[case("foo:1.0.0-rc.1", "foo:1.0.0", Some(Ordering::Less))]
+#[case("foo:1.0.0-rc.2", "foo:1.0.0-rc.10", Some(Ordering::Less))]
+#[case("foo:latest", "foo:1.0.0", None)]
+#[case("foo:1.0.0", "foo:v1.0.0", None)]
+#[case("foo:1.0", "foo:1.0.0", None)]
+#[case("foo", "foo:1.0.0", None)]
+fn cmp_tags_semver(#[case] a: &str, #[case] b: &str, #[case] expected: Option<Ordering>) {
+    let a: Image = a.parse().unwrap();
+    let b: Image = b.parse().unwrap();
+    assert_eq!(a.cmp_tags_semver(&b), expected);
+}
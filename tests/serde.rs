@@ -0,0 +1,161 @@
+#![cfg(feature = "serde")]
+
+use oci_imgref::image::Image;
+use oci_imgref::registry::Registry;
+use oci_imgref::repository::Repository;
+
+const INPUT: &str = "quay.io:443/foo/bar:latest@sha256:e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855";
+
+#[test]
+fn human_readable() {
+    let image: Image = INPUT.parse().unwrap();
+
+    let json = serde_json::to_string(&image).unwrap();
+    assert_eq!(json, format!("\"{INPUT}\""));
+    assert_eq!(serde_json::from_str::<Image>(&json).unwrap(), image);
+
+    let json = serde_json::to_string(&image.repository).unwrap();
+    assert_eq!(json, "\"quay.io:443/foo/bar\"");
+    assert_eq!(
+        serde_json::from_str::<Repository>(&json).unwrap(),
+        image.repository
+    );
+}
+
+#[test]
+fn compact() {
+    let image: Image = INPUT.parse().unwrap();
+
+    let bytes = bincode::serialize(&image).unwrap();
+    assert_ne!(bytes, bincode::serialize(INPUT).unwrap());
+    assert_eq!(bincode::deserialize::<Image>(&bytes).unwrap(), image);
+
+    let registry = image.repository.registry.unwrap();
+    let bytes = bincode::serialize(&registry).unwrap();
+    assert_eq!(bincode::deserialize::<Registry>(&bytes).unwrap(), registry);
+}
+
+#[test]
+fn compact_invalid() {
+    let bytes = bincode::serialize(&("quay-.io", Some(443u16))).unwrap();
+    assert!(bincode::deserialize::<Registry>(&bytes).is_err());
+}
+
+#[rstest::rstest]
+#[case(None, Some("my.app"), "x")]
+#[case(None, Some("localhost"), "x")]
+#[case(None, Some("a/b"), "c")]
+#[case(None, Some("a//b"), "c")]
+#[case(Some(("quay.io", None)), None, "x-")]
+#[case(Some(("quay.io", None)), None, "LONG")]
+fn compact_inconsistent(
+    #[case] registry: Option<(&str, Option<u16>)>,
+    #[case] organization: Option<&str>,
+    #[case] container: &str,
+) {
+    let long = "a".repeat(oci_imgref::MAX_REFERENCE_LEN);
+    let container = container.replace("LONG", &long);
+    let repository = (registry, organization, container);
+
+    let bytes = bincode::serialize(&repository).unwrap();
+    assert!(bincode::deserialize::<Repository>(&bytes).is_err());
+
+    let image = (repository, Some("latest"), None::<()>);
+    let bytes = bincode::serialize(&image).unwrap();
+    assert!(bincode::deserialize::<Image>(&bytes).is_err());
+}
+
+#[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+struct Parent {
+    name: String,
+
+    #[serde(flatten, with = "oci_imgref::image::fields")]
+    image: Image,
+}
+
+#[rstest::rstest]
+#[case(
+    INPUT,
+    r#"{"name":"x","registry":"quay.io:443","repository":"foo/bar","tag":"latest","digest":"sha256:e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"}"#
+)]
+#[case("library/ubuntu", r#"{"name":"x","repository":"library/ubuntu"}"#)]
+#[case(
+    "gcr.io/a/b/c:v1",
+    r#"{"name":"x","registry":"gcr.io","repository":"a/b/c","tag":"v1"}"#
+)]
+fn flatten(#[case] input: &str, #[case] json: &str) {
+    let parent = Parent {
+        name: "x".into(),
+        image: input.parse().unwrap(),
+    };
+
+    assert_eq!(serde_json::to_string(&parent).unwrap(), json);
+    assert_eq!(serde_json::from_str::<Parent>(json).unwrap(), parent);
+}
+
+#[rstest::rstest]
+#[case(r#"{"name":"x"}"#)]
+#[case(r#"{"name":"x","repository":"a/b/c"}"#)]
+#[case(r#"{"name":"x","repository":"my.app/x"}"#)]
+#[case(r#"{"name":"x","repository":"localhost/x"}"#)]
+#[case(r#"{"name":"x","registry":"quay-.io","repository":"foo"}"#)]
+#[case(r#"{"name":"x","repository":"foo","tag":"-bad"}"#)]
+#[case(r#"{"name":"x","repository":"foo","digest":"sha256:e3"}"#)]
+fn flatten_invalid(#[case] json: &str) {
+    assert!(serde_json::from_str::<Parent>(json).is_err());
+}
+
+#[test]
+fn flatten_too_long() {
+    let container = "a".repeat(oci_imgref::MAX_REFERENCE_LEN);
+    let json = format!(r#"{{"name":"x","registry":"quay.io","repository":"{container}"}}"#);
+    assert!(serde_json::from_str::<Parent>(&json).is_err());
+}
+
+#[rstest::rstest]
+#[case("ubuntu")]
+#[case("library/ubuntu:22.04")]
+#[case("Quay.IO/Foo/Bar:Latest")]
+#[case("localhost:5000/a/b/c/d")]
+#[case("[fe80::1%eth0]:5000/foo/bar")]
+#[case("docker.io/library/ubuntu@sha256:e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855")]
+#[case(INPUT)]
+fn round_trip_hash(#[case] input: &str) {
+    use std::hash::{BuildHasher, RandomState};
+
+    let image: Image = input.parse().unwrap();
+    let state = RandomState::new();
+
+    let json: Image = serde_json::from_str(&serde_json::to_string(&image).unwrap()).unwrap();
+    assert_eq!(json, image);
+    assert_eq!(state.hash_one(&json), state.hash_one(&image));
+
+    let compact: Image = bincode::deserialize(&bincode::serialize(&image).unwrap()).unwrap();
+    assert_eq!(compact, image);
+    assert_eq!(state.hash_one(&compact), state.hash_one(&image));
+}
+
+#[test]
+fn static_and_owned() {
+    use std::borrow::Cow;
+
+    const STATIC: Image = Image {
+        repository: Repository::from_static(
+            Some(Registry::docker_hub()),
+            Some("library"),
+            "ubuntu",
+        ),
+        tag: Some(Cow::Borrowed("latest")),
+        digest: None,
+    };
+
+    let owned: Image = "docker.io/library/ubuntu:latest".parse().unwrap();
+
+    let json = serde_json::to_string(&STATIC).unwrap();
+    assert_eq!(json, serde_json::to_string(&owned).unwrap());
+    assert_eq!(serde_json::from_str::<Image>(&json).unwrap(), STATIC);
+
+    let bytes = bincode::serialize(&STATIC).unwrap();
+    assert_eq!(bytes, bincode::serialize(&owned).unwrap());
+    assert_eq!(bincode::deserialize::<Image>(&bytes).unwrap(), owned);
+}
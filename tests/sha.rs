@@ -0,0 +1,19 @@
+#![cfg(feature = "sha")]
+
+use oci_imgref::digest::Digest;
+use oci_imgref::sha::DigestExt;
+
+#[rstest::rstest]
+#[case(
+    b"",
+    "sha256:e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+)]
+#[case(
+    b"abc",
+    "sha256:ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+)]
+fn sha256(#[case] data: &[u8], #[case] expected: &str) {
+    let digest = Digest::sha256(data).unwrap();
+    assert_eq!(digest.to_string(), expected);
+    assert_eq!(digest.to_string().parse::<Digest>().unwrap(), digest);
+}
@@ -0,0 +1,38 @@
+#![cfg(feature = "url")]
+
+use oci_imgref::image::{Error, Image};
+use oci_imgref::registry::Error as RegError;
+use oci_imgref::repository::Error as RepoError;
+
+const DIGEST: &str = "sha256:e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855";
+
+#[rstest::rstest]
+#[case("https://quay.io/foo/bar", Ok("quay.io/foo/bar"))]
+#[case("https://quay.io:443/foo/bar#v1", Ok("quay.io/foo/bar:v1"))]
+#[case("http://localhost:5000/bar#v1", Ok("localhost:5000/bar:v1"))]
+#[case("http://registry/foo/bar", Ok("registry/foo/bar"))]
+#[case(
+    "http://registry/bar",
+    Err(Error::Repository(RepoError::AmbiguousRegistry))
+)]
+#[case("https://gcr.io/a/b/c#DIGEST", Ok("gcr.io/a/b/c@DIGEST"))]
+#[case("https://[::1]:5000/foo", Ok("[::1]:5000/foo"))]
+#[case("ftp://quay.io/foo", Err(Error::Url))]
+#[case("https://user@quay.io/foo", Err(Error::Url))]
+#[case("https://quay.io/foo?tag=v1", Err(Error::Url))]
+#[case("https://quay.io/", Err(Error::Repository(RepoError::Container)))]
+#[case("https://quay.io/foo/", Err(Error::Repository(RepoError::Container)))]
+#[case("https://quay.io/foo#-v1", Err(Error::Tag))]
+#[case(
+    "https://quay_io/foo",
+    Err(Error::Repository(RepoError::Registry(RegError::InvalidChar)))
+)]
+#[case("https://quay.io/foo/LONG", Err(Error::Repository(RepoError::TooLong)))]
+fn try_from_url(#[case] input: &str, #[case] expected: Result<&str, Error>) {
+    let input = input.replace("LONG", &"a".repeat(oci_imgref::MAX_REFERENCE_LEN));
+    let url = url::Url::parse(&input.replace("DIGEST", DIGEST)).unwrap();
+
+    let image = Image::try_from(&url).map(String::from);
+    assert_eq!(image, expected.map(|s| s.replace("DIGEST", DIGEST)));
+    assert_eq!(Image::try_from(url).map(String::from), image);
+}